@@ -0,0 +1,365 @@
+//! Benchmarks for every stage of the Nova Scotia pipeline, on the bundled toy circuit and, when
+//! it has been compiled with `examples/bitcoin/circom/compile.sh 1`, the bitcoin circuit.
+//!
+//! Proving and verifying go through the crate's own folding path (`create_recursive_circuit`,
+//! `IvcProver`, `compress_recursive_snark`), so that regressions in it show up here. The packed
+//! benchmarks fold the same invocations with 1 to 3 of them per step, the `per_iteration_count`
+//! of `examples/bitcoin/benchmark.csv`.
+//!
+//! Run with `cargo bench`. Witness generation backends which cannot run on this machine (e.g. a
+//! C++ witness generator built for another platform, or no `node` installed) are skipped.
+use std::{
+    collections::HashMap,
+    env::temp_dir,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use criterion::{
+    criterion_group, criterion_main, measurement::WallTime, BatchSize, BenchmarkGroup, BenchmarkId,
+    Criterion,
+};
+use ff::PrimeField;
+use nova_scotia::{
+    cancel::CancellationToken,
+    circom::{
+        circuit::R1CS,
+        reader::{
+            generate_witness_from_bin, generate_witness_from_wasm, load_r1cs,
+            WitnessGeneratorOptions,
+        },
+    },
+    compose::{pack_private_inputs, PackedCircuit},
+    compress_recursive_snark, create_public_params, create_recursive_circuit,
+    create_recursive_circuit_composed, create_recursive_circuit_from_witnesses,
+    ivc::IvcProver,
+    verifier::verify_compressed_snark,
+    witness::PrecomputedWitnesses,
+    FileLocation, F, S,
+};
+use nova_snark::{traits::circuit::TrivialCircuit, CompressedSNARK, PublicParams};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+type G1 = pasta_curves::pallas::Point;
+type G2 = pasta_curves::vesta::Point;
+
+const NUM_STEPS: usize = 3;
+/// Invocations of the circuit folded by the packed benchmarks, divisible by every packing.
+const PACKED_INVOCATIONS: usize = 6;
+const PER_ITERATION_COUNTS: [usize; 3] = [1, 2, 3];
+
+struct Target {
+    name: &'static str,
+    r1cs: PathBuf,
+    witness_generator_cpp: PathBuf,
+    witness_generator_wasm: PathBuf,
+    start_public_input: Vec<F<G1>>,
+    /// One private input per invocation, `PACKED_INVOCATIONS` of them.
+    private_inputs: Vec<HashMap<String, Value>>,
+}
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn toy() -> Target {
+    let private_inputs = (0..PACKED_INVOCATIONS)
+        .map(|i| HashMap::from([("adder".to_string(), json!(i))]))
+        .collect();
+
+    Target {
+        name: "toy",
+        r1cs: root().join("examples/toy/pasta/toy.r1cs"),
+        witness_generator_cpp: root().join("examples/toy/pasta/toy_cpp/toy"),
+        witness_generator_wasm: root().join("examples/toy/pasta/toy_js/toy.wasm"),
+        start_public_input: vec![F::<G1>::from(10), F::<G1>::from(10)],
+        private_inputs,
+    }
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct Blocks {
+    prevBlockHash: [String; 2],
+    blockHashes: Vec<[String; 2]>,
+    blockHeaders: Vec<Vec<u8>>,
+}
+
+fn bitcoin() -> Target {
+    let btc_blocks: Blocks =
+        serde_json::from_str(include_str!("../examples/bitcoin/fetcher/btc-blocks.json")).unwrap();
+
+    let private_inputs = (0..PACKED_INVOCATIONS)
+        .map(|i| {
+            HashMap::from([
+                (
+                    "blockHashes".to_string(),
+                    json!(btc_blocks.blockHashes[i..i + 1]),
+                ),
+                (
+                    "blockHeaders".to_string(),
+                    json!(btc_blocks.blockHeaders[i..i + 1]),
+                ),
+            ])
+        })
+        .collect();
+
+    Target {
+        name: "bitcoin",
+        r1cs: root().join("examples/bitcoin/circom/bitcoin_benchmark.r1cs"),
+        witness_generator_cpp: root()
+            .join("examples/bitcoin/circom/bitcoin_benchmark_cpp/bitcoin_benchmark"),
+        witness_generator_wasm: root()
+            .join("examples/bitcoin/circom/bitcoin_benchmark_js/bitcoin_benchmark.wasm"),
+        start_public_input: vec![
+            F::<G1>::from_str_vartime(&btc_blocks.prevBlockHash[0]).unwrap(),
+            F::<G1>::from_str_vartime(&btc_blocks.prevBlockHash[1]).unwrap(),
+        ],
+        private_inputs,
+    }
+}
+
+fn can_run(program: &Path, arg: &str) -> bool {
+    program.exists() && Command::new(program).arg(arg).output().is_ok()
+}
+
+fn node_available() -> bool {
+    Command::new("node").arg("--version").output().is_ok()
+}
+
+fn witness_input_json(z: &[F<G1>], private_input: &HashMap<String, Value>) -> String {
+    let mut input = private_input.clone();
+    let step_in = z
+        .iter()
+        .map(|x| num_bigint::BigUint::from_bytes_le(x.to_repr().as_ref()).to_str_radix(10))
+        .collect::<Vec<_>>();
+    input.insert("step_in".to_string(), json!(step_in));
+    serde_json::to_string(&input).unwrap()
+}
+
+/// Compute the witness of each of the first `NUM_STEPS` steps, chaining the outputs of each step
+/// into the next one.
+fn step_witnesses(
+    target: &Target,
+    r1cs: &R1CS<F<G1>>,
+    generate_witness: impl Fn(&String, &Path) -> Vec<F<G1>>,
+) -> Vec<Vec<F<G1>>> {
+    let witness_output = temp_dir().join("nova_scotia_bench.wtns");
+    let mut z = target.start_public_input.clone();
+    let mut witnesses = vec![];
+    for private_input in &target.private_inputs[..NUM_STEPS] {
+        let witness = generate_witness(&witness_input_json(&z, private_input), &witness_output);
+        z = r1cs.public_outputs(&witness);
+        witnesses.push(witness);
+    }
+    witnesses
+}
+
+fn bench_target(c: &mut Criterion, target: Target) {
+    if !target.r1cs.exists() {
+        eprintln!(
+            "skipping {} benchmarks, {} not found",
+            target.name,
+            target.r1cs.display()
+        );
+        return;
+    }
+
+    let mut group = c.benchmark_group(target.name);
+    group.sample_size(10);
+
+    let r1cs_file = FileLocation::PathBuf(target.r1cs.clone());
+    group.bench_function("load_r1cs", |b| b.iter(|| load_r1cs::<G1, G2>(&r1cs_file)));
+    let r1cs = load_r1cs::<G1, G2>(&r1cs_file);
+
+    // the witnesses of the first steps, and the witness generator proving runs
+    let mut witnesses = None;
+    let mut witness_generator_file = None;
+    let input_json = witness_input_json(&target.start_public_input, &target.private_inputs[0]);
+    let witness_output = temp_dir().join("nova_scotia_bench.wtns");
+    let options = WitnessGeneratorOptions::for_r1cs(&r1cs);
+    let cancel = CancellationToken::new();
+
+    if can_run(&target.witness_generator_cpp, "--help") {
+        let cpp = &target.witness_generator_cpp;
+        group.bench_function("witness_generation/cpp", |b| {
+            b.iter(|| {
+                generate_witness_from_bin::<F<G1>>(
+                    cpp,
+                    &input_json,
+                    &witness_output,
+                    &options,
+                    &cancel,
+                )
+            })
+        });
+        witnesses = Some(step_witnesses(&target, &r1cs, |input, output| {
+            generate_witness_from_bin(cpp, input, output, &options, &cancel).unwrap()
+        }));
+        witness_generator_file = Some(FileLocation::PathBuf(cpp.clone()));
+    } else {
+        eprintln!("skipping {} C++ witness generation", target.name);
+    }
+
+    if target.witness_generator_wasm.exists() && node_available() {
+        let wasm = FileLocation::PathBuf(target.witness_generator_wasm.clone());
+        group.bench_function("witness_generation/wasm", |b| {
+            b.iter(|| {
+                generate_witness_from_wasm::<F<G1>>(
+                    &wasm,
+                    &input_json,
+                    &witness_output,
+                    &options,
+                    &cancel,
+                )
+            })
+        });
+        if witnesses.is_none() {
+            witnesses = Some(step_witnesses(&target, &r1cs, |input, output| {
+                generate_witness_from_wasm(&wasm, input, output, &options, &cancel).unwrap()
+            }));
+            witness_generator_file = Some(wasm);
+        }
+    } else {
+        eprintln!("skipping {} wasm witness generation", target.name);
+    }
+
+    group.bench_function("public_params_setup", |b| {
+        b.iter(|| create_public_params::<G1, G2>(r1cs.clone()))
+    });
+
+    let (Some(witnesses), Some(witness_generator_file)) = (witnesses, witness_generator_file)
+    else {
+        eprintln!(
+            "skipping {} proving, no witness generator available",
+            target.name
+        );
+        return;
+    };
+    let pp = create_public_params::<G1, G2>(r1cs.clone());
+    let z0 = &target.start_public_input;
+
+    group.bench_function("create_recursive_circuit", |b| {
+        b.iter(|| {
+            create_recursive_circuit(
+                witness_generator_file.clone(),
+                r1cs.clone(),
+                target.private_inputs[..NUM_STEPS].to_vec(),
+                z0.clone(),
+                &pp,
+            )
+            .unwrap()
+        })
+    });
+
+    group.bench_function("create_recursive_circuit_from_witnesses", |b| {
+        b.iter(|| {
+            create_recursive_circuit_from_witnesses(
+                r1cs.clone(),
+                witnesses.clone(),
+                z0.clone(),
+                &pp,
+            )
+            .unwrap()
+        })
+    });
+
+    // fold the step after the base case, which creating the proof already covers
+    let first_step = create_recursive_circuit_from_witnesses(
+        r1cs.clone(),
+        witnesses[..1].to_vec(),
+        z0.clone(),
+        &pp,
+    )
+    .unwrap();
+    group.bench_function("prove_step", |b| {
+        b.iter_batched(
+            || {
+                IvcProver::resume(
+                    &pp,
+                    r1cs.clone(),
+                    PrecomputedWitnesses::new(vec![witnesses[1].clone()]),
+                    first_step.clone(),
+                )
+            },
+            |mut prover| {
+                prover.step(&target.private_inputs[1]).unwrap();
+                prover
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    let recursive_proof =
+        create_recursive_circuit_from_witnesses(r1cs.clone(), witnesses, z0.clone(), &pp).unwrap();
+    group.bench_function("verify", |b| {
+        b.iter(|| recursive_proof.verify(&pp).unwrap())
+    });
+
+    let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
+    let recursive_snark = recursive_proof.recursive_snark();
+    group.bench_function("compress", |b| {
+        b.iter(|| compress_recursive_snark(&pp, &pk, recursive_snark).unwrap())
+    });
+
+    let compressed_snark = compress_recursive_snark(&pp, &pk, recursive_snark).unwrap();
+    let z0_secondary = [F::<G2>::from(0)];
+    group.bench_function("verify_compressed", |b| {
+        b.iter(|| {
+            verify_compressed_snark(&vk, &compressed_snark, NUM_STEPS, z0, &z0_secondary).unwrap()
+        })
+    });
+
+    bench_packed(&mut group, &target, &r1cs, &witness_generator_file);
+    group.finish();
+}
+
+/// Fold `PACKED_INVOCATIONS` invocations of the circuit, packed `per_iteration_count` per step.
+fn bench_packed(
+    group: &mut BenchmarkGroup<WallTime>,
+    target: &Target,
+    r1cs: &R1CS<F<G1>>,
+    witness_generator_file: &FileLocation,
+) {
+    for per_iteration_count in PER_ITERATION_COUNTS {
+        let circuit = PackedCircuit::packed(
+            r1cs.clone(),
+            witness_generator_file.clone(),
+            per_iteration_count,
+        )
+        .unwrap();
+        let pp = PublicParams::<G1, G2, _, _>::setup(&circuit, &TrivialCircuit::default());
+        let private_inputs =
+            pack_private_inputs(target.private_inputs.clone(), per_iteration_count).unwrap();
+
+        group.bench_with_input(
+            BenchmarkId::new("create_recursive_circuit_composed", per_iteration_count),
+            &private_inputs,
+            |b, private_inputs| {
+                b.iter(|| {
+                    create_recursive_circuit_composed(
+                        circuit.clone(),
+                        private_inputs.clone(),
+                        target.start_public_input.clone(),
+                        &pp,
+                        &mut (),
+                        &CancellationToken::new(),
+                    )
+                    .unwrap()
+                })
+            },
+        );
+    }
+}
+
+fn bench_toy(c: &mut Criterion) {
+    bench_target(c, toy());
+}
+
+fn bench_bitcoin(c: &mut Criterion) {
+    bench_target(c, bitcoin());
+}
+
+criterion_group!(benches, bench_toy, bench_bitcoin);
+criterion_main!(benches);
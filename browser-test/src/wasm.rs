@@ -5,7 +5,7 @@ use nova_scotia::{
     create_public_params, create_recursive_circuit, FileLocation, F, S,
 };
 use nova_snark::{
-    traits::{circuit::TrivialTestCircuit, Group},
+    traits::{circuit::TrivialCircuit, Group},
     CompressedSNARK, PublicParams,
 };
 use serde_json::json;
@@ -81,7 +81,7 @@ pub async fn generate_proof(pp_str: String) -> String {
     let start_public_input = [F::<G1>::from(10), F::<G1>::from(10)];
 
     let pp = serde_json::from_str::<
-        PublicParams<G1, G2, CircomCircuit<F<G1>>, TrivialTestCircuit<F<G2>>>,
+        PublicParams<G1, G2, CircomCircuit<F<G1>>, TrivialCircuit<F<G2>>>,
     >(&pp_str)
     .unwrap();
 
@@ -104,7 +104,7 @@ pub async fn generate_proof(pp_str: String) -> String {
     );
 
     console_log!("Creating a RecursiveSNARK...");
-    let recursive_snark = create_recursive_circuit(
+    let recursive_proof = create_recursive_circuit(
         witness_generator_wasm,
         r1cs,
         private_inputs,
@@ -114,18 +114,19 @@ pub async fn generate_proof(pp_str: String) -> String {
     .await
     .unwrap();
 
-    // TODO: empty?
-    let z0_secondary = [F::<G2>::zero()];
-
     // verify the recursive SNARK
     console_log!("Verifying a RecursiveSNARK...");
-    let res = recursive_snark.verify(&pp, iteration_count, &start_public_input, &z0_secondary);
+    let res = recursive_proof.verify(&pp);
     assert!(res.is_ok());
 
     // produce a compressed SNARK
     console_log!("Generating a CompressedSNARK using Spartan with IPA-PC...");
     let (pk, _vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
-    let res = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::prove(&pp, &pk, &recursive_snark);
+    let res = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::prove(
+        &pp,
+        &pk,
+        recursive_proof.recursive_snark(),
+    );
     assert!(res.is_ok());
     let compressed_snark = res.unwrap();
     return serde_json::to_string(&compressed_snark).unwrap();
@@ -134,7 +135,7 @@ pub async fn generate_proof(pp_str: String) -> String {
 #[wasm_bindgen]
 pub async fn verify_compressed_proof(pp_str: String, proof_str: String) -> bool {
     let pp = serde_json::from_str::<
-        PublicParams<G1, G2, CircomCircuit<F<G1>>, TrivialTestCircuit<F<G2>>>,
+        PublicParams<G1, G2, CircomCircuit<F<G1>>, TrivialCircuit<F<G2>>>,
     >(&pp_str)
     .unwrap();
     let (_pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
@@ -143,7 +144,7 @@ pub async fn verify_compressed_proof(pp_str: String, proof_str: String) -> bool
     let z0_secondary = vec![F::<G2>::zero()];
 
     let compressed_proof = serde_json::from_str::<
-        CompressedSNARK<G1, G2, CircomCircuit<F<G1>>, TrivialTestCircuit<F<G2>>, S<G1>, S<G2>>,
+        CompressedSNARK<G1, G2, CircomCircuit<F<G1>>, TrivialCircuit<F<G2>>, S<G1>, S<G2>>,
     >(&proof_str)
     .unwrap();
     let res = compressed_proof.verify(
@@ -7,9 +7,14 @@ use std::{
 
 use ff::PrimeField;
 use nova_scotia::{
-    circom::reader::load_r1cs, create_public_params, create_recursive_circuit, FileLocation, F,
+    cancel::CancellationToken,
+    circom::reader::load_r1cs,
+    compose::{pack_private_inputs, PackedCircuit},
+    create_recursive_circuit_composed,
+    progress::StepProgress,
+    FileLocation, F,
 };
-use nova_snark::traits::Group;
+use nova_snark::{traits::circuit::TrivialCircuit, PublicParams};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -41,30 +46,32 @@ fn bench(iteration_count: usize, per_iteration_count: usize) -> (Duration, Durat
         F::<G1>::from_str_vartime(&btc_blocks.prevBlockHash[1]).unwrap(),
     ];
 
+    // the circuit verifies a single block, and we pack per_iteration_count of them per step
     let mut private_inputs = Vec::new();
 
-    for i in 0..iteration_count {
+    for i in 0..iteration_count * per_iteration_count {
         let mut private_input = HashMap::new();
         private_input.insert(
             "blockHashes".to_string(),
-            json!(
-                btc_blocks.blockHashes
-                    [i * per_iteration_count..i * per_iteration_count + per_iteration_count]
-            ),
+            json!(btc_blocks.blockHashes[i..i + 1]),
         );
         private_input.insert(
             "blockHeaders".to_string(),
-            json!(
-                btc_blocks.blockHeaders
-                    [i * per_iteration_count..i * per_iteration_count + per_iteration_count]
-            ),
+            json!(btc_blocks.blockHeaders[i..i + 1]),
         );
         private_inputs.push(private_input);
     }
+    let private_inputs = pack_private_inputs(private_inputs, per_iteration_count).unwrap();
 
     // println!("{:?} {:?}", start_public_input, private_inputs);
 
-    let pp = create_public_params::<G1, G2>(r1cs.clone());
+    let circuit = PackedCircuit::packed(
+        r1cs,
+        FileLocation::PathBuf(witness_generator_file),
+        per_iteration_count,
+    )
+    .unwrap();
+    let pp = PublicParams::<G1, G2, _, _>::setup(&circuit, &TrivialCircuit::default());
 
     println!(
         "Number of constraints per step (primary circuit): {}",
@@ -86,23 +93,28 @@ fn bench(iteration_count: usize, per_iteration_count: usize) -> (Duration, Durat
 
     println!("Creating a RecursiveSNARK...");
     let start = Instant::now();
-    let recursive_snark = create_recursive_circuit(
-        FileLocation::PathBuf(witness_generator_file),
-        r1cs,
+    let recursive_proof = create_recursive_circuit_composed(
+        circuit,
         private_inputs,
         start_public_input.to_vec(),
         &pp,
+        &mut |progress: &StepProgress<F<G1>>| {
+            println!(
+                "Step {}: witness generation took {:?}, folding took {:?}",
+                progress.step, progress.witness_generation_time, progress.fold_time
+            )
+        },
+        &CancellationToken::new(),
     )
-    .unwrap();
+    .unwrap()
+    .into_recursive_proof();
     let prover_time = start.elapsed();
     println!("RecursiveSNARK creation took {:?}", start.elapsed());
 
-    let z0_secondary = [<G2 as Group>::Scalar::zero()];
-
     // verify the recursive SNARK
     println!("Verifying a RecursiveSNARK...");
     let start = Instant::now();
-    let res = recursive_snark.verify(&pp, iteration_count, &start_public_input, &z0_secondary);
+    let res = recursive_proof.verify(&pp);
     println!(
         "RecursiveSNARK::verify: {:?}, took {:?}",
         res,
@@ -148,16 +160,17 @@ fn main() {
     let mut file = std::fs::File::create("examples/bitcoin/benchmark.csv").unwrap();
     file.write_all(b"iteration_count,per_iteration_count,prover_time,verifier_time\n")
         .unwrap();
+
+    // compile the single block circuit once, the packing factor is chosen at runtime
+    std::process::Command::new("bash")
+        .arg("examples/bitcoin/circom/compile.sh")
+        .arg("1")
+        .output()
+        .expect("failed to execute process");
+
     for i in 1..=5 {
         let j = 120 / i;
 
-        // run bash script
-        std::process::Command::new("bash")
-            .arg("examples/bitcoin/circom/compile.sh")
-            .arg(i.to_string())
-            .output()
-            .expect("failed to execute process");
-
         let (prover_time, verifier_time) = bench(j, i);
         file.write_all(format!("{},{},{:?},{:?}\n", j, i, prover_time, verifier_time).as_bytes())
             .unwrap();
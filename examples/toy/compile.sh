@@ -4,5 +4,14 @@ circom ./examples/toy/toy.circom --r1cs --wasm --sym --c --output ./examples/toy
 cd examples/toy/pasta/toy_cpp && make
 cd -
 
+# official circom has no secq256k1 prime, so this needs a circom build that adds it
+if circom --help | grep -q secq256k1; then
+    circom ./examples/toy/toy.circom --r1cs --wasm --sym --c --output ./examples/toy/secq256k1/ --prime secq256k1
+    cd examples/toy/secq256k1/toy_cpp && make
+    cd -
+else
+    echo "skipping secq256k1: this circom has no --prime secq256k1" >&2
+fi
+
 circom ./examples/toy/toy.circom --r1cs --wasm --sym --c --output ./examples/toy/bn254/ --prime bn128
 cd examples/toy/bn254/toy_cpp && make
\ No newline at end of file
@@ -54,7 +54,7 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
 
     println!("Creating a RecursiveSNARK...");
     let start = Instant::now();
-    let mut recursive_snark = create_recursive_circuit(
+    let mut recursive_proof = create_recursive_circuit(
         FileLocation::PathBuf(witness_generator_file.clone()),
         r1cs.clone(),
         private_inputs,
@@ -64,13 +64,10 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     .unwrap();
     println!("RecursiveSNARK creation took {:?}", start.elapsed());
 
-    // TODO: empty?
-    let z0_secondary = [F::<G2>::from(0)];
-
     // verify the recursive SNARK
     println!("Verifying a RecursiveSNARK...");
     let start = Instant::now();
-    let res = recursive_snark.verify(&pp, iteration_count, &start_public_input, &z0_secondary);
+    let res = recursive_proof.verify(&pp);
     println!(
         "RecursiveSNARK::verify: {:?}, took {:?}",
         res,
@@ -78,7 +75,7 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     );
     assert!(res.is_ok());
 
-    let z_last = res.unwrap().0;
+    let z_last = res.unwrap();
 
     assert_eq!(z_last[0], F::<G1>::from(20));
     assert_eq!(z_last[1], F::<G1>::from(70));
@@ -87,7 +84,11 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     println!("Generating a CompressedSNARK using Spartan with IPA-PC...");
     let start = Instant::now();
     let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
-    let res = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::prove(&pp, &pk, &recursive_snark);
+    let res = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::prove(
+        &pp,
+        &pk,
+        recursive_proof.recursive_snark(),
+    );
     println!(
         "CompressedSNARK::prove: {:?}, took {:?}",
         res.is_ok(),
@@ -101,9 +102,9 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     let start = Instant::now();
     let res = compressed_snark.verify(
         &vk,
-        iteration_count,
-        start_public_input.to_vec(),
-        z0_secondary.to_vec(),
+        recursive_proof.num_steps(),
+        recursive_proof.z0_primary().to_vec(),
+        recursive_proof.z0_secondary().to_vec(),
     );
     println!(
         "CompressedSNARK::verify: {:?}, took {:?}",
@@ -126,12 +127,10 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     }
 
     let res = continue_recursive_circuit(
-        &mut recursive_snark,
-        z_last,
+        &mut recursive_proof,
         FileLocation::PathBuf(witness_generator_file),
         r1cs,
         private_inputs_continue,
-        start_public_input.to_vec(),
         &pp,
     );
     assert!(res.is_ok());
@@ -143,7 +142,7 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     // verify the recursive SNARK with the added steps
     println!("Verifying a RecursiveSNARK...");
     let start = Instant::now();
-    let res = recursive_snark.verify(&pp, iteration_count + iteration_count_continue, &start_public_input, &z0_secondary);
+    let res = recursive_proof.verify(&pp);
     println!(
         "RecursiveSNARK::verify: {:?}, took {:?}",
         res,
@@ -151,8 +150,8 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     );
     assert!(res.is_ok());
 
-    assert_eq!(res.clone().unwrap().0[0], F::<G1>::from(31));
-    assert_eq!(res.unwrap().0[1], F::<G1>::from(115));
+    assert_eq!(res.clone().unwrap()[0], F::<G1>::from(31));
+    assert_eq!(res.unwrap()[1], F::<G1>::from(115));
 }
 
 fn main() {
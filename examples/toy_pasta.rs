@@ -3,11 +3,7 @@ use std::{collections::HashMap, env::current_dir, time::Instant};
 use nova_scotia::{
     circom::reader::load_r1cs, create_public_params, create_recursive_circuit, FileLocation, F, S,
 };
-use nova_snark::{
-    provider,
-    traits::{circuit::StepCircuit, Group},
-    CompressedSNARK, PublicParams,
-};
+use nova_snark::{CompressedSNARK, PublicParams};
 use serde_json::json;
 
 fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
@@ -57,7 +53,7 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
 
     println!("Creating a RecursiveSNARK...");
     let start = Instant::now();
-    let recursive_snark = create_recursive_circuit(
+    let recursive_proof = create_recursive_circuit(
         FileLocation::PathBuf(witness_generator_file),
         r1cs,
         private_inputs,
@@ -67,13 +63,10 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     .unwrap();
     println!("RecursiveSNARK creation took {:?}", start.elapsed());
 
-    // TODO: empty?
-    let z0_secondary = [F::<G2>::from(0)];
-
     // verify the recursive SNARK
     println!("Verifying a RecursiveSNARK...");
     let start = Instant::now();
-    let res = recursive_snark.verify(&pp, iteration_count, &start_public_input, &z0_secondary);
+    let res = recursive_proof.verify(&pp);
     println!(
         "RecursiveSNARK::verify: {:?}, took {:?}",
         res,
@@ -86,7 +79,11 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     let start = Instant::now();
 
     let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
-    let res = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::prove(&pp, &pk, &recursive_snark);
+    let res = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::prove(
+        &pp,
+        &pk,
+        recursive_proof.recursive_snark(),
+    );
     println!(
         "CompressedSNARK::prove: {:?}, took {:?}",
         res.is_ok(),
@@ -100,9 +97,9 @@ fn run_test(circuit_filepath: String, witness_gen_filepath: String) {
     let start = Instant::now();
     let res = compressed_snark.verify(
         &vk,
-        iteration_count,
-        start_public_input.to_vec(),
-        z0_secondary.to_vec(),
+        recursive_proof.num_steps(),
+        recursive_proof.z0_primary().to_vec(),
+        recursive_proof.z0_secondary().to_vec(),
     );
     println!(
         "CompressedSNARK::verify: {:?}, took {:?}",
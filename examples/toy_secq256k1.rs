@@ -0,0 +1,95 @@
+use std::time::Instant;
+
+use nova_scotia::{
+    circom::reader::load_r1cs, create_public_params, create_recursive_circuit_from_witnesses,
+    FileLocation, F, S,
+};
+use nova_snark::{
+    provider::secp_secq::{secp256k1, secq256k1},
+    CompressedSNARK, PublicParams,
+};
+
+fn main() {
+    type G1 = secq256k1::Point;
+    type G2 = secp256k1::Point;
+
+    // The toy circuit's R1CS over secp256k1's base field, in the JSON format of
+    // `snarkjs r1cs export json`. Its witness is [1, step_out, step_in], so it is computed here
+    // instead of running a witness generator.
+    let iteration_count = 5;
+    let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+        "examples/toy/secq256k1/toy.r1cs.json".into(),
+    ));
+
+    let start_public_input = [F::<G1>::from(10), F::<G1>::from(10)];
+    let mut z = start_public_input;
+    let mut witnesses = Vec::new();
+    for adder in 0..iteration_count {
+        let step_out = [z[0] + F::<G1>::from(adder), z[0] + z[1]];
+        witnesses.push(vec![F::<G1>::from(1), step_out[0], step_out[1], z[0], z[1]]);
+        z = step_out;
+    }
+
+    let pp: PublicParams<G1, G2, _, _> = create_public_params(r1cs.clone());
+
+    println!(
+        "Number of constraints per step (primary circuit): {}",
+        pp.num_constraints().0
+    );
+    println!(
+        "Number of constraints per step (secondary circuit): {}",
+        pp.num_constraints().1
+    );
+
+    println!("Creating a RecursiveSNARK...");
+    let start = Instant::now();
+    let recursive_proof =
+        create_recursive_circuit_from_witnesses(r1cs, witnesses, start_public_input.to_vec(), &pp)
+            .unwrap();
+    println!("RecursiveSNARK creation took {:?}", start.elapsed());
+
+    // verify the recursive SNARK
+    println!("Verifying a RecursiveSNARK...");
+    let start = Instant::now();
+    let res = recursive_proof.verify(&pp);
+    println!(
+        "RecursiveSNARK::verify: {:?}, took {:?}",
+        res,
+        start.elapsed()
+    );
+    assert_eq!(res.unwrap(), z);
+
+    // produce a compressed SNARK
+    println!("Generating a CompressedSNARK using Spartan with IPA-PC...");
+    let start = Instant::now();
+
+    let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
+    let res = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::prove(
+        &pp,
+        &pk,
+        recursive_proof.recursive_snark(),
+    );
+    println!(
+        "CompressedSNARK::prove: {:?}, took {:?}",
+        res.is_ok(),
+        start.elapsed()
+    );
+    assert!(res.is_ok());
+    let compressed_snark = res.unwrap();
+
+    // verify the compressed SNARK
+    println!("Verifying a CompressedSNARK...");
+    let start = Instant::now();
+    let res = compressed_snark.verify(
+        &vk,
+        recursive_proof.num_steps(),
+        recursive_proof.z0_primary().to_vec(),
+        recursive_proof.z0_secondary().to_vec(),
+    );
+    println!(
+        "CompressedSNARK::verify: {:?}, took {:?}",
+        res.is_ok(),
+        start.elapsed()
+    );
+    assert!(res.is_ok());
+}
@@ -0,0 +1,380 @@
+//! EVM calldata for compressed proofs exported by `export::export_compressed_snark`, for a
+//! Solidity verifier to read. The calldata is the ABI encoding of a single `uint256[]` argument,
+//! without a function selector: the offset `0x20`, the number of words, then the words
+//! themselves, each big-endian.
+//!
+//! The words are the fields of a `CompressedSNARKJson` in the order they are documented in
+//! `export`, with every list preceded by its length and every point written as `x, y`:
+//!
+//! ```text
+//! version, num_steps, z0[], z_n[], z0_secondary[],
+//! r_u_primary, r_w_snark_primary, r_u_secondary, l_u_secondary, nifs_secondary,
+//! f_w_snark_secondary, zn_primary[], zn_secondary[]
+//! ```
+//!
+//! where a relaxed instance is `comm_w, comm_e, x[], u`, an instance is `comm_w, x[]`, a
+//! Spartan proof is `sc_proof_outer, claims_outer (3 words), eval_e, sc_proof_inner, eval_w,
+//! sc_proof_batch, evals_batch[], l_vec[], r_vec[], a_hat` and a sum-check proof is the number of
+//! rounds followed by each round's coefficients as a list. The curve cycle and nova-snark version
+//! are not encoded, since a verifier contract is written for one of each.
+
+use std::io;
+
+use num_bigint::{BigInt, Sign};
+
+use crate::export::{
+    CompressedSNARKJson, IpaJson, NifsJson, NovaProofJson, PointJson, R1CSInstanceJson,
+    RelaxedR1CSInstanceJson, SpartanProofJson, SumcheckProofJson, NOVA_SNARK_VERSION,
+};
+
+const WORD: usize = 32;
+
+/// Encode `proof` as the calldata of a Solidity verifier taking a `uint256[]`.
+pub fn encode_calldata(proof: &CompressedSNARKJson) -> Result<Vec<u8>, io::Error> {
+    let mut words = Words::default();
+    words.usize(proof.version as usize);
+    words.usize(proof.num_steps);
+    words.list(&proof.z0)?;
+    words.list(&proof.z_n)?;
+    words.list(&proof.z0_secondary)?;
+
+    let nova = &proof.proof;
+    words.relaxed_instance(&nova.r_u_primary)?;
+    words.spartan(&nova.r_w_snark_primary)?;
+    words.relaxed_instance(&nova.r_u_secondary)?;
+    words.point(&nova.l_u_secondary.comm_w)?;
+    words.list(&nova.l_u_secondary.x)?;
+    words.point(&nova.nifs_secondary.comm_t)?;
+    words.spartan(&nova.f_w_snark_secondary)?;
+    words.list(&nova.zn_primary)?;
+    words.list(&nova.zn_secondary)?;
+
+    let mut calldata = Vec::with_capacity((words.0.len() + 2) * WORD);
+    calldata.extend(word(WORD));
+    calldata.extend(word(words.0.len()));
+    for value in &words.0 {
+        calldata.extend(value);
+    }
+    Ok(calldata)
+}
+
+/// Decode calldata written by `encode_calldata` for a proof over `curve_cycle`, failing if it is
+/// truncated, has trailing bytes or holds a value that is not a `usize` where a length is
+/// expected.
+pub fn decode_calldata(
+    calldata: &[u8],
+    curve_cycle: &str,
+) -> Result<CompressedSNARKJson, io::Error> {
+    if !calldata.len().is_multiple_of(WORD) {
+        return Err(invalid(format!(
+            "calldata is {} bytes, not a whole number of words",
+            calldata.len()
+        )));
+    }
+    let mut reader = Reader {
+        words: calldata.chunks_exact(WORD),
+    };
+    if reader.usize()? != WORD {
+        return Err(invalid(
+            "calldata does not start with the offset 0x20".to_string(),
+        ));
+    }
+    let len = reader.usize()?;
+    if len != reader.words.len() {
+        return Err(invalid(format!(
+            "calldata holds {} words, but says it holds {}",
+            reader.words.len(),
+            len
+        )));
+    }
+
+    let version = reader.usize()?;
+    let proof = CompressedSNARKJson {
+        version: u32::try_from(version)
+            .map_err(|_| invalid(format!("version {} is too large", version)))?,
+        curve_cycle: curve_cycle.to_string(),
+        nova_snark_version: NOVA_SNARK_VERSION.to_string(),
+        num_steps: reader.usize()?,
+        z0: reader.list()?,
+        z_n: reader.list()?,
+        z0_secondary: reader.list()?,
+        proof: NovaProofJson {
+            r_u_primary: reader.relaxed_instance()?,
+            r_w_snark_primary: reader.spartan()?,
+            r_u_secondary: reader.relaxed_instance()?,
+            l_u_secondary: R1CSInstanceJson {
+                comm_w: reader.point()?,
+                x: reader.list()?,
+            },
+            nifs_secondary: NifsJson {
+                comm_t: reader.point()?,
+            },
+            f_w_snark_secondary: reader.spartan()?,
+            zn_primary: reader.list()?,
+            zn_secondary: reader.list()?,
+        },
+    };
+    if reader.words.len() != 0 {
+        return Err(invalid(format!(
+            "calldata has {} words after the proof",
+            reader.words.len()
+        )));
+    }
+    Ok(proof)
+}
+
+/// A `usize` as a big-endian word.
+fn word(value: usize) -> [u8; WORD] {
+    let mut word = [0u8; WORD];
+    word[WORD - 8..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[derive(Default)]
+struct Words(Vec<[u8; WORD]>);
+
+impl Words {
+    fn usize(&mut self, value: usize) {
+        self.0.push(word(value));
+    }
+
+    fn decimal(&mut self, value: &str) -> Result<(), io::Error> {
+        let (sign, bytes) = value
+            .parse::<BigInt>()
+            .map_err(|_| invalid(format!("{:?} is not an integer in decimal", value)))?
+            .to_bytes_be();
+        if sign == Sign::Minus || bytes.len() > WORD {
+            return Err(invalid(format!("{} does not fit in a uint256", value)));
+        }
+        let mut word = [0u8; WORD];
+        word[WORD - bytes.len()..].copy_from_slice(&bytes);
+        self.0.push(word);
+        Ok(())
+    }
+
+    fn list(&mut self, values: &[String]) -> Result<(), io::Error> {
+        self.usize(values.len());
+        values.iter().try_for_each(|value| self.decimal(value))
+    }
+
+    fn point(&mut self, point: &PointJson) -> Result<(), io::Error> {
+        self.decimal(&point.x)?;
+        self.decimal(&point.y)
+    }
+
+    fn points(&mut self, points: &[PointJson]) -> Result<(), io::Error> {
+        self.usize(points.len());
+        points.iter().try_for_each(|point| self.point(point))
+    }
+
+    fn relaxed_instance(&mut self, instance: &RelaxedR1CSInstanceJson) -> Result<(), io::Error> {
+        self.point(&instance.comm_w)?;
+        self.point(&instance.comm_e)?;
+        self.list(&instance.x)?;
+        self.decimal(&instance.u)
+    }
+
+    fn sumcheck(&mut self, proof: &SumcheckProofJson) -> Result<(), io::Error> {
+        self.usize(proof.compressed_polys.len());
+        proof
+            .compressed_polys
+            .iter()
+            .try_for_each(|coeffs| self.list(coeffs))
+    }
+
+    fn spartan(&mut self, proof: &SpartanProofJson) -> Result<(), io::Error> {
+        self.sumcheck(&proof.sc_proof_outer)?;
+        proof
+            .claims_outer
+            .iter()
+            .try_for_each(|claim| self.decimal(claim))?;
+        self.decimal(&proof.eval_e)?;
+        self.sumcheck(&proof.sc_proof_inner)?;
+        self.decimal(&proof.eval_w)?;
+        self.sumcheck(&proof.sc_proof_batch)?;
+        self.list(&proof.evals_batch)?;
+        self.points(&proof.eval_arg.l_vec)?;
+        self.points(&proof.eval_arg.r_vec)?;
+        self.decimal(&proof.eval_arg.a_hat)
+    }
+}
+
+struct Reader<'a> {
+    words: std::slice::ChunksExact<'a, u8>,
+}
+
+impl Reader<'_> {
+    fn next(&mut self) -> Result<&[u8], io::Error> {
+        self.words
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "calldata ends mid-proof"))
+    }
+
+    fn usize(&mut self) -> Result<usize, io::Error> {
+        let word = self.next()?;
+        let (high, low) = word.split_at(WORD - 8);
+        let value = u64::from_be_bytes(low.try_into().unwrap());
+        if high.iter().any(|&byte| byte != 0) || value > usize::MAX as u64 {
+            return Err(invalid(format!(
+                "0x{} is too large for a length",
+                hex(word)
+            )));
+        }
+        Ok(value as usize)
+    }
+
+    /// A length, which can't be more than the words left since every item takes at least one.
+    fn len(&mut self) -> Result<usize, io::Error> {
+        let len = self.usize()?;
+        if len > self.words.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "calldata has a list of {} items, but only {} words left",
+                    len,
+                    self.words.len()
+                ),
+            ));
+        }
+        Ok(len)
+    }
+
+    fn decimal(&mut self) -> Result<String, io::Error> {
+        Ok(BigInt::from_bytes_be(Sign::Plus, self.next()?).to_string())
+    }
+
+    fn list(&mut self) -> Result<Vec<String>, io::Error> {
+        let len = self.len()?;
+        (0..len).map(|_| self.decimal()).collect()
+    }
+
+    fn point(&mut self) -> Result<PointJson, io::Error> {
+        Ok(PointJson {
+            x: self.decimal()?,
+            y: self.decimal()?,
+        })
+    }
+
+    fn points(&mut self) -> Result<Vec<PointJson>, io::Error> {
+        let len = self.len()?;
+        (0..len).map(|_| self.point()).collect()
+    }
+
+    fn relaxed_instance(&mut self) -> Result<RelaxedR1CSInstanceJson, io::Error> {
+        Ok(RelaxedR1CSInstanceJson {
+            comm_w: self.point()?,
+            comm_e: self.point()?,
+            x: self.list()?,
+            u: self.decimal()?,
+        })
+    }
+
+    fn sumcheck(&mut self) -> Result<SumcheckProofJson, io::Error> {
+        let len = self.len()?;
+        Ok(SumcheckProofJson {
+            compressed_polys: (0..len).map(|_| self.list()).collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn spartan(&mut self) -> Result<SpartanProofJson, io::Error> {
+        Ok(SpartanProofJson {
+            sc_proof_outer: self.sumcheck()?,
+            claims_outer: [self.decimal()?, self.decimal()?, self.decimal()?],
+            eval_e: self.decimal()?,
+            sc_proof_inner: self.sumcheck()?,
+            eval_w: self.decimal()?,
+            sc_proof_batch: self.sumcheck()?,
+            evals_batch: self.list()?,
+            eval_arg: IpaJson {
+                l_vec: self.points()?,
+                r_vec: self.points()?,
+                a_hat: self.decimal()?,
+            },
+        })
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(feature = "prover")]
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn calldata_round_trip() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::export::{export_compressed_snark, export_verifier_key};
+        use crate::{
+            compress_recursive_snark, create_public_params, create_recursive_circuit, FileLocation,
+            C1, C2, F, S,
+        };
+        use ff::Field;
+        use nova_snark::{provider::bn256_grumpkin, CompressedSNARK};
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        type G1 = bn256_grumpkin::bn256::Point;
+        type G2 = bn256_grumpkin::grumpkin::Point;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/bn254");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+        let private_inputs = (0..2)
+            .map(|adder| HashMap::from([("adder".to_string(), serde_json::json!(adder))]))
+            .collect::<Vec<_>>();
+        let z0 = vec![F::<G1>::from(10), F::<G1>::from(10)];
+        let recursive_proof = create_recursive_circuit(
+            FileLocation::PathBuf(root.join("toy_js/toy.wasm")),
+            r1cs,
+            private_inputs,
+            z0.clone(),
+            &pp,
+        )
+        .unwrap();
+        let z_n = recursive_proof.verify(&pp).unwrap();
+        let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
+        let compressed_snark =
+            compress_recursive_snark(&pp, &pk, recursive_proof.recursive_snark()).unwrap();
+        let vk = export_verifier_key(&vk).unwrap();
+        let proof =
+            export_compressed_snark(&compressed_snark, 2, &z0, &z_n, &[F::<G2>::ZERO]).unwrap();
+
+        let calldata = encode_calldata(&proof).unwrap();
+        assert_eq!(calldata.len() % WORD, 0);
+        assert_eq!(calldata[..WORD], word(WORD));
+        assert_eq!(calldata[WORD..2 * WORD], word(calldata.len() / WORD - 2));
+        // version 1, 2 steps, then z0 = [10, 10]
+        assert_eq!(calldata[2 * WORD..3 * WORD], word(1));
+        assert_eq!(calldata[3 * WORD..4 * WORD], word(2));
+        assert_eq!(calldata[4 * WORD..5 * WORD], word(2));
+        assert_eq!(calldata[5 * WORD..6 * WORD], word(10));
+
+        let decoded = decode_calldata(&calldata, "bn256_grumpkin").unwrap();
+        assert_eq!(decoded, proof);
+        decoded.verify::<G1, G2, C1<G1>, C2<G2>>(&vk).unwrap();
+
+        let err =
+            decode_calldata(&calldata[..calldata.len() - WORD], "bn256_grumpkin").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let mut truncated = calldata[..calldata.len() - WORD].to_vec();
+        truncated[WORD..2 * WORD].copy_from_slice(&word(calldata.len() / WORD - 3));
+        let err = decode_calldata(&truncated, "bn256_grumpkin").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let mut trailing = calldata.clone();
+        trailing.extend(word(0));
+        trailing[WORD..2 * WORD].copy_from_slice(&word(calldata.len() / WORD - 1));
+        let err = decode_calldata(&trailing, "bn256_grumpkin").unwrap_err();
+        assert_eq!(err.to_string(), "calldata has 1 words after the proof");
+
+        let mut too_large = proof;
+        too_large.z0[0] = BigInt::from(2).pow(256).to_string();
+        let err = encode_calldata(&too_large).unwrap_err();
+        assert!(err.to_string().ends_with("does not fit in a uint256"));
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Token used to cancel a proving run from another thread (or from a `ProgressObserver`).
+/// Proving stops before the next step, and a running C++ or node witness generator is killed.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How far a cancelled proving run got. Proving can be resumed with the `continue_` functions
+/// from `z_i`, with the private inputs from index `num_steps` on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cancelled<Fr> {
+    /// Number of steps folded before the run was cancelled.
+    pub num_steps: usize,
+    /// The primary `z_i` after the last folded step.
+    pub z_i: Vec<Fr>,
+}
+
+/// Result of a proving run which may have been cancelled.
+pub enum ProvingOutcome<RS, Fr> {
+    /// Every step was folded.
+    Completed(RS),
+    /// The run was cancelled, and the proof only covers the steps folded until then.
+    Cancelled(RS, Cancelled<Fr>),
+}
+
+impl<RS, Fr> ProvingOutcome<RS, Fr> {
+    /// The proof, whether or not the run completed.
+    pub fn into_recursive_proof(self) -> RS {
+        match self {
+            ProvingOutcome::Completed(recursive_proof) => recursive_proof,
+            ProvingOutcome::Cancelled(recursive_proof, _) => recursive_proof,
+        }
+    }
+}
+
+/// The error returned when witness generation is cancelled.
+pub(crate) fn cancelled_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "witness generation cancelled")
+}
+
+/// The outcome of a `create_` proving function which folded `num_steps` out of `total_steps`.
+pub(crate) fn proving_outcome<RS, Fr>(
+    recursive_snark: Option<RS>,
+    num_steps: usize,
+    total_steps: usize,
+    z_i: Vec<Fr>,
+) -> Result<ProvingOutcome<RS, Fr>, io::Error> {
+    match recursive_snark {
+        None => Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "proving cancelled before the first step was folded",
+        )),
+        Some(recursive_snark) if num_steps == total_steps => {
+            Ok(ProvingOutcome::Completed(recursive_snark))
+        }
+        Some(recursive_snark) => Ok(ProvingOutcome::Cancelled(
+            recursive_snark,
+            Cancelled { num_steps, z_i },
+        )),
+    }
+}
+
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn cancel_and_resume() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::progress::StepProgress;
+        use crate::witness::NodeWitnessGenerator;
+        use crate::{
+            continue_recursive_circuit, create_public_params,
+            create_recursive_circuit_with_secondary, FileLocation,
+        };
+        use ff::Field;
+        use nova_snark::traits::circuit::TrivialCircuit;
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as nova_snark::traits::Group>::Scalar;
+        type Fq = <G2 as nova_snark::traits::Group>::Scalar;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            root.join("examples/toy/pasta/toy.r1cs"),
+        ));
+        let witness_generator_file =
+            FileLocation::PathBuf(root.join("examples/toy/pasta/toy_js/toy.wasm"));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+
+        let private_inputs = (0..4)
+            .map(|adder| HashMap::from([("adder".to_string(), serde_json::json!(adder))]))
+            .collect::<Vec<_>>();
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+
+        // cancel once the second step has been folded
+        let cancel = CancellationToken::new();
+        let witness_generator =
+            NodeWitnessGenerator::new(root.join("examples/toy/pasta/toy_js/toy.wasm")).unwrap();
+        let outcome = create_recursive_circuit_with_secondary(
+            &witness_generator,
+            r1cs.clone(),
+            private_inputs.clone(),
+            z0.clone(),
+            vec![TrivialCircuit::default(); private_inputs.len()],
+            vec![Fq::ZERO],
+            &pp,
+            &mut |progress: &StepProgress<Fr>| {
+                if progress.step == 1 {
+                    cancel.cancel();
+                }
+            },
+            &cancel,
+        )
+        .unwrap();
+
+        // (10, 10) -> (10, 20) -> (11, 30)
+        let (mut recursive_proof, cancelled) = match outcome {
+            ProvingOutcome::Cancelled(recursive_proof, cancelled) => (recursive_proof, cancelled),
+            ProvingOutcome::Completed(_) => panic!("proving was not cancelled"),
+        };
+        assert_eq!(cancelled.num_steps, 2);
+        assert_eq!(cancelled.z_i, vec![Fr::from(11), Fr::from(30)]);
+        assert_eq!(recursive_proof.num_steps(), 2);
+        assert_eq!(recursive_proof.verify(&pp).unwrap(), cancelled.z_i);
+
+        // -> (13, 41) -> (16, 54)
+        continue_recursive_circuit(
+            &mut recursive_proof,
+            witness_generator_file,
+            r1cs,
+            private_inputs[cancelled.num_steps..].to_vec(),
+            &pp,
+        )
+        .unwrap();
+        let z_i = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(z_i, vec![Fr::from(16), Fr::from(54)]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cancel_kills_running_witness_generator() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::progress::StepProgress;
+        use crate::witness::{
+            CircomInput, CppWitnessGenerator, PrecomputedWitnesses, WitnessGenerator,
+        };
+        use crate::{create_public_params, create_recursive_circuit_with_secondary, FileLocation};
+        use ff::Field;
+        use nova_snark::traits::circuit::TrivialCircuit;
+        use std::collections::HashMap;
+        use std::os::unix::fs::PermissionsExt;
+        use std::path::PathBuf;
+        use std::time::{Duration, Instant};
+        use std::{fs, thread};
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as nova_snark::traits::Group>::Scalar;
+        type Fq = <G2 as nova_snark::traits::Group>::Scalar;
+
+        /// Replays the first step's witness, then runs a generator that never finishes.
+        struct Hanging {
+            first: PrecomputedWitnesses<Fr>,
+            hanging: CppWitnessGenerator,
+        }
+
+        impl WitnessGenerator<Fr> for Hanging {
+            fn generate(
+                &self,
+                input: &CircomInput,
+                cancel: &CancellationToken,
+            ) -> Result<Vec<Fr>, io::Error> {
+                self.first
+                    .generate(input, cancel)
+                    .or_else(|_| self.hanging.generate(input, cancel))
+            }
+        }
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            root.join("examples/toy/pasta/toy.r1cs"),
+        ));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+
+        let dir = std::env::temp_dir().join(format!("nova_scotia_cancel_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let witness_bin = dir.join("witness_generator");
+        fs::write(&witness_bin, "#!/bin/sh\nsleep 60\n").unwrap();
+        fs::set_permissions(&witness_bin, fs::Permissions::from_mode(0o755)).unwrap();
+        let witness_generator = Hanging {
+            // (10, 10) -> (10, 20)
+            first: PrecomputedWitnesses::new(vec![[1, 10, 20, 10, 10].map(Fr::from).to_vec()]),
+            hanging: CppWitnessGenerator {
+                witness_output: dir.join("witness.wtns"),
+                ..CppWitnessGenerator::new(witness_bin).unwrap()
+            },
+        };
+
+        // cancel while the second step's generator is running
+        let cancel = CancellationToken::new();
+        let start = Instant::now();
+        let outcome = create_recursive_circuit_with_secondary(
+            &witness_generator,
+            r1cs,
+            vec![HashMap::<String, serde_json::Value>::new(); 2],
+            vec![Fr::from(10), Fr::from(10)],
+            vec![TrivialCircuit::default(); 2],
+            vec![Fq::ZERO],
+            &pp,
+            &mut |_: &StepProgress<Fr>| {
+                let cancel = cancel.clone();
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(200));
+                    cancel.cancel();
+                });
+            },
+            &cancel,
+        )
+        .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(30));
+
+        let (recursive_proof, cancelled) = match outcome {
+            ProvingOutcome::Cancelled(recursive_proof, cancelled) => (recursive_proof, cancelled),
+            ProvingOutcome::Completed(_) => panic!("proving was not cancelled"),
+        };
+        assert_eq!(cancelled.num_steps, 1);
+        assert_eq!(recursive_proof.verify(&pp).unwrap(), cancelled.z_i);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,5 +1,9 @@
 pub mod circuit;
+#[cfg(feature = "prover")]
 pub mod file;
+#[cfg(feature = "prover")]
 pub mod reader;
-#[cfg(target_family = "wasm")]
+#[cfg(feature = "prover")]
+pub mod sym;
+#[cfg(all(feature = "prover", target_family = "wasm"))]
 pub mod wasm;
@@ -1,30 +1,41 @@
-use bellperson::gadgets::num::AllocatedNum;
+use bellpepper_core::num::AllocatedNum;
 use nova_snark::traits::circuit::StepCircuit;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::str;
 
-use bellperson::{ConstraintSystem, LinearCombination, SynthesisError};
+use bellpepper_core::{ConstraintSystem, LinearCombination, SynthesisError};
 use ff::PrimeField;
 
+/// An R1CS in the JSON format of snarkjs's `r1cs export json`.
 #[derive(Serialize, Deserialize)]
 pub struct CircuitJson {
-    pub constraints: Vec<Vec<BTreeMap<String, String>>>,
+    /// The field's modulus in decimal, checked against the scalar field when present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prime: Option<String>,
+    /// `[a, b, c]` linear combinations per constraint, mapping wire indices to decimal
+    /// coefficients.
+    pub constraints: Vec<Vec<BTreeMap<usize, String>>>,
     #[serde(rename = "nPubInputs")]
     pub num_inputs: usize,
     #[serde(rename = "nOutputs")]
     pub num_outputs: usize,
     #[serde(rename = "nVars")]
     pub num_variables: usize,
+    #[serde(rename = "nPrvInputs", default)]
+    pub num_private_inputs: usize,
 }
 
 pub type Constraint<Fr> = (Vec<(usize, Fr)>, Vec<(usize, Fr)>, Vec<(usize, Fr)>);
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct R1CS<Fr: PrimeField> {
     pub num_inputs: usize,
     pub num_aux: usize,
     pub num_variables: usize,
+    /// Number of private input signals of the main component, which may have been optimized
+    /// out of the variables.
+    pub num_private_inputs: usize,
     pub constraints: Vec<Constraint<Fr>>,
 }
 
@@ -35,27 +46,56 @@ pub struct CircomCircuit<Fr: PrimeField> {
     // debug symbols
 }
 
-impl<'a, Fr: PrimeField> CircomCircuit<Fr> {
+impl<Fr: PrimeField> R1CS<Fr> {
+    /// Number of public outputs, assuming exactly half of the public inputs and outputs are
+    /// outputs.
+    fn num_public_outputs(&self) -> usize {
+        (self.num_inputs - 1) / 2
+    }
+
+    /// The `step_out` of `witness`.
+    pub fn public_outputs(&self, witness: &[Fr]) -> Vec<Fr> {
+        witness[1..1 + self.num_public_outputs()].to_vec()
+    }
+
+    /// The `step_in` `witness` was computed for.
+    pub fn public_inputs(&self, witness: &[Fr]) -> Vec<Fr> {
+        witness[1 + self.num_public_outputs()..self.num_inputs].to_vec()
+    }
+
+    /// Index of the first constraint `witness` doesn't satisfy, if any.
+    pub fn first_unsatisfied_constraint(&self, witness: &[Fr]) -> Option<usize> {
+        let eval = |lc: &[(usize, Fr)]| {
+            lc.iter().fold(Fr::ZERO, |acc, (index, coeff)| {
+                acc + *coeff * witness[*index]
+            })
+        };
+        self.constraints
+            .iter()
+            .position(|(a, b, c)| eval(a) * eval(b) != eval(c))
+    }
+}
+
+impl<Fr: PrimeField> CircomCircuit<Fr> {
     pub fn get_public_outputs(&self) -> Vec<Fr> {
-        // NOTE: assumes exactly half of the (public inputs + outputs) are outputs
-        let pub_output_count = (self.r1cs.num_inputs - 1) / 2;
-        let mut z_out: Vec<Fr> = vec![];
-        for i in 1..self.r1cs.num_inputs {
-            // Public inputs do not exist, so we alloc, and later enforce equality from z values
-            let f: Fr = {
-                match &self.witness {
-                    None => Fr::ONE,
-                    Some(w) => w[i],
-                }
-            };
+        match &self.witness {
+            None => vec![Fr::ONE; self.r1cs.num_public_outputs()],
+            Some(w) => self.r1cs.public_outputs(w),
+        }
+    }
 
-            if i <= pub_output_count {
-                // public output
-                z_out.push(f);
-            }
+    /// The `step_in` the witness was computed for.
+    pub fn get_public_inputs(&self) -> Vec<Fr> {
+        match &self.witness {
+            None => vec![Fr::ONE; self.r1cs.num_inputs - 1 - self.r1cs.num_public_outputs()],
+            Some(w) => self.r1cs.public_inputs(w),
         }
+    }
 
-        z_out
+    /// Index of the first constraint the witness doesn't satisfy, if any.
+    pub fn first_unsatisfied_constraint(&self) -> Option<usize> {
+        self.r1cs
+            .first_unsatisfied_constraint(self.witness.as_ref()?)
     }
 
     pub fn vanilla_synthesize<CS: ConstraintSystem<Fr>>(
@@ -82,7 +122,13 @@ impl<'a, Fr: PrimeField> CircomCircuit<Fr> {
         let pub_output_count = (self.r1cs.num_inputs - 1) / 2;
 
         for i in 1..self.r1cs.num_inputs {
-            // Public inputs do not exist, so we alloc, and later enforce equality from z values
+            if i > pub_output_count {
+                // public input, which is wired directly to the incoming z value
+                vars.push(z[i - 1 - pub_output_count].clone());
+                continue;
+            }
+
+            // public output
             let f: Fr = {
                 match witness {
                     None => Fr::ONE,
@@ -92,10 +138,7 @@ impl<'a, Fr: PrimeField> CircomCircuit<Fr> {
             let v = AllocatedNum::alloc(cs.namespace(|| format!("public_{}", i)), || Ok(f))?;
 
             vars.push(v.clone());
-            if i <= pub_output_count {
-                // public output
-                z_out.push(v);
-            }
+            z_out.push(v);
         }
         for i in 0..self.r1cs.num_aux {
             // Private witness trace
@@ -132,20 +175,11 @@ impl<'a, Fr: PrimeField> CircomCircuit<Fr> {
             );
         }
 
-        for i in (pub_output_count + 1)..self.r1cs.num_inputs {
-            cs.enforce(
-                || format!("pub input enforce {}", i),
-                |lc| lc + z[i - 1 - pub_output_count].get_variable(),
-                |lc| lc + CS::one(),
-                |lc| lc + vars[i - 1].get_variable(),
-            );
-        }
-
         Ok(z_out)
     }
 }
 
-impl<'a, Fr: PrimeField> StepCircuit<Fr> for CircomCircuit<Fr> {
+impl<Fr: PrimeField> StepCircuit<Fr> for CircomCircuit<Fr> {
     fn arity(&self) -> usize {
         (self.r1cs.num_inputs - 1) / 2
     }
@@ -156,12 +190,51 @@ impl<'a, Fr: PrimeField> StepCircuit<Fr> for CircomCircuit<Fr> {
         z: &[AllocatedNum<Fr>],
     ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
         // synthesize the circuit
-        let z_out = self.vanilla_synthesize(cs, z);
-
-        z_out
+        self.vanilla_synthesize(cs, z)
     }
+}
+
+#[cfg(feature = "prover")]
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn public_inputs_reuse_z() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::{create_public_params, FileLocation};
+        use bellpepper_core::{test_cs::TestConstraintSystem, Comparable};
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as nova_snark::traits::Group>::Scalar;
+
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta/toy.r1cs"),
+        ));
+        let circuit = CircomCircuit {
+            r1cs: r1cs.clone(),
+            witness: None,
+        };
 
-    fn output(&self, _z: &[Fr]) -> Vec<Fr> {
-        self.get_public_outputs()
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let z = (0..circuit.arity())
+            .map(|i| {
+                AllocatedNum::alloc(cs.namespace(|| format!("z{}", i)), || {
+                    Ok(<Fr as ff::Field>::ONE)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let num_aux = cs.aux().len();
+        circuit.synthesize(&mut cs, &z).unwrap();
+
+        // only the circuit's own constraints, and no variables for step_in
+        assert_eq!(cs.num_constraints(), r1cs.constraints.len());
+        assert_eq!(cs.aux().len() - num_aux, r1cs.num_aux + circuit.arity());
+
+        // used to be 9819, with a duplicate variable and an equality constraint per step_in
+        let pp = create_public_params::<G1, G2>(r1cs);
+        assert_eq!(pp.num_constraints().0, 9817);
     }
 }
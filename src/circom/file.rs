@@ -4,7 +4,6 @@
 use crate::circom::circuit::Constraint;
 use byteorder::{LittleEndian, ReadBytesExt};
 use ff::PrimeField;
-use hex_literal::hex;
 use nova_snark::traits::Group;
 use std::{
     collections::HashMap,
@@ -39,8 +38,12 @@ pub(crate) fn read_field<R: Read, Fr: PrimeField>(mut reader: R) -> Result<Fr> {
         // TODO: may need to reverse order?
         *digit = reader.read_u8()?;
     }
-    let fr = Fr::from_repr(repr).unwrap();
-    Ok(fr)
+    Option::from(Fr::from_repr(repr)).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            "field element is not less than the field's modulus",
+        )
+    })
 }
 
 fn read_header<R: Read>(mut reader: R, size: u64) -> Result<Header> {
@@ -71,7 +74,8 @@ fn read_constraint_vec<R: Read, Fr: PrimeField>(
     header: &Header,
 ) -> Result<Vec<(usize, Fr)>> {
     let n_vec = reader.read_u32::<LittleEndian>()? as usize;
-    let mut vec = Vec::with_capacity(n_vec);
+    // don't trust the count of a corrupt file with the allocation
+    let mut vec = Vec::with_capacity(n_vec.min(1024));
     for _ in 0..n_vec {
         vec.push((
             reader.read_u32::<LittleEndian>()? as usize,
@@ -87,7 +91,7 @@ fn read_constraints<R: Read, Fr: PrimeField>(
     header: &Header,
 ) -> Result<Vec<Constraint<Fr>>> {
     // todo check section size
-    let mut vec = Vec::with_capacity(header.n_constraints as usize);
+    let mut vec = Vec::with_capacity((header.n_constraints as usize).min(1024));
     for _ in 0..header.n_constraints {
         vec.push((
             read_constraint_vec::<&mut R, Fr>(&mut reader, header)?,
@@ -109,7 +113,7 @@ fn read_map<R: Read>(mut reader: R, size: u64, header: &Header) -> Result<Vec<u6
     for _ in 0..header.n_wires {
         vec.push(reader.read_u64::<LittleEndian>()?);
     }
-    if vec[0] != 0 {
+    if vec.first() != Some(&0) {
         return Err(Error::new(
             ErrorKind::InvalidData,
             "Wire 0 should always be mapped to 0",
@@ -145,7 +149,7 @@ where
     for _ in 0..num_sections {
         let section_type = reader.read_u32::<LittleEndian>()?;
         let section_size = reader.read_u64::<LittleEndian>()?;
-        let offset = reader.seek(SeekFrom::Current(0))?;
+        let offset = reader.stream_position()?;
         section_offsets.insert(section_type, offset);
         section_sizes.insert(section_type, section_size);
         reader.seek(SeekFrom::Current(section_size as i64))?;
@@ -154,9 +158,20 @@ where
     let header_type = 1;
     let constraint_type = 2;
     let wire2label_type = 3;
+    let section = |section_type: u32| match (
+        section_offsets.get(&section_type),
+        section_sizes.get(&section_type),
+    ) {
+        (Some(&offset), Some(&size)) => Ok((offset, size)),
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Missing section {}", section_type),
+        )),
+    };
 
-    reader.seek(SeekFrom::Start(*section_offsets.get(&header_type).unwrap()))?;
-    let header = read_header(&mut reader, *section_sizes.get(&header_type).unwrap())?;
+    let (offset, size) = section(header_type)?;
+    reader.seek(SeekFrom::Start(offset))?;
+    let header = read_header(&mut reader, size)?;
     if header.field_size != 32 {
         return Err(Error::new(
             ErrorKind::InvalidData,
@@ -173,23 +188,14 @@ where
     //     ));
     // }
 
-    reader.seek(SeekFrom::Start(
-        *section_offsets.get(&constraint_type).unwrap(),
-    ))?;
-    let constraints = read_constraints::<&mut R, <G1 as Group>::Scalar>(
-        &mut reader,
-        *section_sizes.get(&constraint_type).unwrap(),
-        &header,
-    )?;
+    let (offset, size) = section(constraint_type)?;
+    reader.seek(SeekFrom::Start(offset))?;
+    let constraints =
+        read_constraints::<&mut R, <G1 as Group>::Scalar>(&mut reader, size, &header)?;
 
-    reader.seek(SeekFrom::Start(
-        *section_offsets.get(&wire2label_type).unwrap(),
-    ))?;
-    let wire_mapping = read_map(
-        &mut reader,
-        *section_sizes.get(&wire2label_type).unwrap(),
-        &header,
-    )?;
+    let (offset, size) = section(wire2label_type)?;
+    reader.seek(SeekFrom::Start(offset))?;
+    let wire_mapping = read_map(&mut reader, size, &header)?;
 
     Ok(R1CSFile {
         version,
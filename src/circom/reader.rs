@@ -1,102 +1,322 @@
 use anyhow::bail;
 use byteorder::{LittleEndian, ReadBytesExt};
-use itertools::Itertools;
 use std::collections::BTreeMap;
-use std::env::current_dir;
 use std::fs;
 use std::fs::{File, OpenOptions};
-use std::io::{BufReader, Read, Seek};
+use std::io::{self, BufReader, Cursor, Read, Seek};
 use std::path::Path;
-use std::process::Command;
-use std::str;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+#[cfg(not(target_family = "wasm"))]
+use std::{collections::BTreeSet, path::PathBuf, sync::Mutex};
 
+use crate::cancel::{cancelled_error, CancellationToken};
 use crate::circom::circuit::{CircuitJson, R1CS};
 use crate::circom::file::{from_reader, read_field};
+#[cfg(not(target_family = "wasm"))]
 use crate::FileLocation;
 use ff::PrimeField;
 use nova_snark::traits::Group;
+use num_bigint::BigInt;
+use num_traits::Num;
+#[cfg(not(target_family = "wasm"))]
+use sha2::{Digest, Sha256};
+
+/// Limits and checks applied when running an external witness generator process.
+#[derive(Clone, Debug, Default)]
+pub struct WitnessGeneratorOptions {
+    /// Kill the process, and fail with a `TimedOut` error, if it runs for longer than this.
+    pub timeout: Option<Duration>,
+    /// Number of values the produced witness must have, i.e. the R1CS's `num_variables`.
+    pub witness_len: Option<usize>,
+}
 
+impl WitnessGeneratorOptions {
+    /// Options checking that the witness matches `r1cs`, without a timeout.
+    pub fn for_r1cs<Fr: PrimeField>(r1cs: &R1CS<Fr>) -> Self {
+        Self {
+            timeout: None,
+            witness_len: Some(r1cs.num_variables),
+        }
+    }
+}
+
+/// Run the C++ witness generator `witness_bin` on `witness_input_json`. The process is killed,
+/// and an `Interrupted` error returned, if `cancel` is cancelled before it exits.
 pub fn generate_witness_from_bin<Fr: PrimeField>(
     witness_bin: &Path,
     witness_input_json: &String,
     witness_output: &Path,
-) -> Vec<Fr> {
-    let root = current_dir().unwrap();
-    let witness_generator_input = root.join("circom_input.json");
-    fs::write(&witness_generator_input, witness_input_json).unwrap();
+    options: &WitnessGeneratorOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<Fr>, io::Error> {
+    // next to the output, so generators writing to different outputs can run concurrently
+    let witness_generator_input = witness_output.with_extension("input.json");
+    fs::write(&witness_generator_input, witness_input_json)?;
 
-    let output = Command::new(witness_bin)
-        .arg(&witness_generator_input)
-        .arg(witness_output)
-        .output()
-        .expect("failed to execute process");
-    if output.stdout.len() > 0 || output.stderr.len() > 0 {
-        print!("stdout: {}", str::from_utf8(&output.stdout).unwrap());
-        print!("stderr: {}", str::from_utf8(&output.stderr).unwrap());
-    }
+    let mut command = Command::new(witness_bin);
+    command.arg(&witness_generator_input).arg(witness_output);
+    let output = run_witness_generator(command, witness_output, options, cancel);
     let _ = fs::remove_file(witness_generator_input);
-    load_witness_from_file(witness_output)
+    output?;
+
+    load_generated_witness(witness_output, options)
 }
 
+/// Run the wasm witness generator `witness_wasm` with node on `witness_input_json`. The process
+/// is killed, and an `Interrupted` error returned, if `cancel` is cancelled before it exits.
 #[cfg(not(target_family = "wasm"))]
 pub fn generate_witness_from_wasm<Fr: PrimeField>(
-    witness_wasm: &FileLocation,
+    witness_wasm_location: &FileLocation,
     witness_input_json: &String,
     witness_output: &Path,
-) -> Vec<Fr> {
-    let witness_wasm = match witness_wasm {
-        FileLocation::PathBuf(path) => path,
-        FileLocation::URL(_) => panic!("unreachable"),
-    };
-
-    let root = current_dir().unwrap();
-    let witness_generator_input = root.join("circom_input.json");
-    fs::write(&witness_generator_input, witness_input_json).unwrap();
+    options: &WitnessGeneratorOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<Fr>, io::Error> {
+    let witness_wasm = witness_wasm_file(witness_wasm_location, witness_output)?;
+    let witness_generator_input = witness_output.with_extension("input.json");
+    fs::write(&witness_generator_input, witness_input_json)?;
 
     let witness_js = Path::new(concat!(
         env!("CARGO_MANIFEST_DIR"),
         "/src/circom/wasm_deps/generate_witness.js"
     ));
-    let output = Command::new("node")
+    let mut command = Command::new("node");
+    command
         .arg(witness_js)
-        .arg(witness_wasm)
+        .arg(&witness_wasm)
         .arg(&witness_generator_input)
-        .arg(witness_output)
-        .output()
-        .expect("failed to execute process");
-    if output.stdout.len() > 0 || output.stderr.len() > 0 {
-        print!("stdout: {}", str::from_utf8(&output.stdout).unwrap());
-        print!("stderr: {}", str::from_utf8(&output.stderr).unwrap());
-    }
+        .arg(witness_output);
+    let output = run_witness_generator(command, witness_output, options, cancel);
     let _ = fs::remove_file(witness_generator_input);
-    load_witness_from_file(witness_output)
+    output?;
+
+    load_generated_witness(witness_output, options)
+}
+
+/// Fetched and in-memory witness generators this process wrote to disk.
+#[cfg(not(target_family = "wasm"))]
+static WRITTEN_WITNESS_WASMS: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+/// A file node can run `witness_wasm_location` from. Fetched and in-memory generators are written
+/// next to `witness_output` at the first step, named by a hash of their URL or contents, and
+/// reused by the following steps, so a URL is only fetched once per process.
+#[cfg(not(target_family = "wasm"))]
+fn witness_wasm_file(
+    witness_wasm_location: &FileLocation,
+    witness_output: &Path,
+) -> Result<PathBuf, io::Error> {
+    let hash = match witness_wasm_location {
+        FileLocation::PathBuf(path) => return Ok(path.clone()),
+        FileLocation::URL(url) => Sha256::new().chain_update("url:").chain_update(url),
+        FileLocation::Bytes(bytes) => Sha256::new().chain_update("bytes:").chain_update(bytes),
+    }
+    .finalize();
+    let hash = hash[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    let path = witness_output.with_extension(format!("{}.generator.wasm", hash));
+
+    // held while fetching, so concurrent steps don't fetch the same generator twice
+    let mut written = WRITTEN_WITNESS_WASMS.lock().unwrap();
+    if !written.contains(&path) || !path.exists() {
+        fs::write(&path, witness_wasm_location.read()?)?;
+        written.insert(path.clone());
+    }
+    Ok(path)
 }
 
-/// load witness file by filename with autodetect encoding (bin or json).
-pub fn load_witness_from_file<Fr: PrimeField>(filename: &Path) -> Vec<Fr> {
-    if filename.ends_with("json") {
+/// How often a running witness generator is checked for exit, timeout or cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Run a witness generator process to completion, capturing its output. The process is killed
+/// if it times out or `cancel` is cancelled first, and fails with its stderr if it exits with a
+/// non-zero status. `witness_output` is removed beforehand, so a stale witness is never read.
+fn run_witness_generator(
+    mut command: Command,
+    witness_output: &Path,
+    options: &WitnessGeneratorOptions,
+    cancel: &CancellationToken,
+) -> Result<Output, io::Error> {
+    let _ = fs::remove_file(witness_output);
+
+    let program = command.get_program().to_string_lossy().into_owned();
+    let start = Instant::now();
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| io::Error::new(err.kind(), format!("failed to run {}: {}", program, err)))?;
+
+    // drain the pipes on other threads, so a chatty process can't block on a full pipe
+    let read_pipe = |pipe: Option<Box<dyn Read + Send>>| {
+        thread::spawn(move || {
+            let mut buffer = vec![];
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buffer);
+            }
+            buffer
+        })
+    };
+    let stdout = read_pipe(child.stdout.take().map(|pipe| Box::new(pipe) as _));
+    let stderr = read_pipe(child.stderr.take().map(|pipe| Box::new(pipe) as _));
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let error = if cancel.is_cancelled() {
+            Some(cancelled_error())
+        } else {
+            options
+                .timeout
+                .filter(|&timeout| start.elapsed() > timeout)
+                .map(|timeout| {
+                    io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("{} timed out after {:?}", program, timeout),
+                    )
+                })
+        };
+        if let Some(error) = error {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = fs::remove_file(witness_output);
+            return Err(error);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let output = Output {
+        status,
+        stdout: stdout.join().unwrap(),
+        stderr: stderr.join().unwrap(),
+    };
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{} failed with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        )));
+    }
+    if !output.stdout.is_empty() || !output.stderr.is_empty() {
+        tracing::debug!(
+            program,
+            stdout = %String::from_utf8_lossy(&output.stdout).trim_end(),
+            stderr = %String::from_utf8_lossy(&output.stderr).trim_end(),
+            "witness generator output"
+        );
+    }
+    Ok(output)
+}
+
+/// Load the `.wtns` file written by a witness generator, checking that it exists and has the
+/// expected length.
+fn load_generated_witness<Fr: PrimeField>(
+    witness_output: &Path,
+    options: &WitnessGeneratorOptions,
+) -> Result<Vec<Fr>, io::Error> {
+    let file = File::open(witness_output).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!(
+                "witness generator did not produce {}: {}",
+                witness_output.display(),
+                err
+            ),
+        )
+    })?;
+    let witness = load_witness_from_bin_reader::<Fr, _>(BufReader::new(file)).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid witness {}: {}", witness_output.display(), err),
+        )
+    })?;
+    check_witness_len(&witness, options)?;
+    Ok(witness)
+}
+
+/// Check that a generated witness has the length expected by `options`.
+pub(crate) fn check_witness_len<Fr>(
+    witness: &[Fr],
+    options: &WitnessGeneratorOptions,
+) -> Result<(), io::Error> {
+    match options.witness_len {
+        Some(witness_len) if witness.len() != witness_len => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "witness has {} values, but the R1CS has {} variables",
+                witness.len(),
+                witness_len
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// load witness file by filename with autodetect encoding (bin, or json for a `.json` extension).
+pub fn load_witness_from_file<Fr: PrimeField>(filename: &Path) -> Result<Vec<Fr>, io::Error> {
+    if filename.extension().unwrap_or_default() == "json" {
         load_witness_from_json_file::<Fr>(filename)
     } else {
-        load_witness_from_bin_file::<Fr>(filename)
+        let reader = BufReader::new(File::open(filename)?);
+        load_witness_from_bin_reader::<Fr, _>(reader)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
     }
 }
 
-/// load witness from json file by filename
-pub fn load_witness_from_json_file<Fr: PrimeField>(filename: &Path) -> Vec<Fr> {
-    let reader = OpenOptions::new()
-        .read(true)
-        .open(filename)
-        .expect("unable to open.");
-    load_witness_from_json::<Fr, BufReader<File>>(BufReader::new(reader))
+/// load witness from json file by filename, see `load_witness_from_json`
+pub fn load_witness_from_json_file<Fr: PrimeField>(filename: &Path) -> Result<Vec<Fr>, io::Error> {
+    load_witness_from_json::<Fr, _>(BufReader::new(File::open(filename)?))
 }
 
-/// load witness from json by a reader
-fn load_witness_from_json<Fr: PrimeField, R: Read>(reader: R) -> Vec<Fr> {
-    let witness: Vec<String> = serde_json::from_reader(reader).expect("unable to read.");
+/// load witness from json by a reader: an array of values as in snarkjs's `wtns export json`,
+/// each a decimal or `0x` hex string or a JSON integer. Values must be less than the field's
+/// prime in absolute value, and negative ones are taken modulo the prime.
+pub fn load_witness_from_json<Fr: PrimeField, R: Read>(reader: R) -> Result<Vec<Fr>, io::Error> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let modulus = field_modulus::<Fr>();
+
+    let witness: Vec<serde_json::Value> = serde_json::from_reader(reader)?;
     witness
-        .into_iter()
-        .map(|x| Fr::from_str_vartime(&x).unwrap())
-        .collect::<Vec<Fr>>()
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let parsed = match value {
+                serde_json::Value::String(x) => match x.strip_prefix("0x") {
+                    Some(hex) => BigInt::from_str_radix(hex, 16).ok(),
+                    None => match x.strip_prefix("-0x") {
+                        Some(hex) => BigInt::from_str_radix(hex, 16).ok().map(|x| -x),
+                        None => x.parse::<BigInt>().ok(),
+                    },
+                },
+                serde_json::Value::Number(x) => x
+                    .as_i64()
+                    .map(BigInt::from)
+                    .or_else(|| x.as_u64().map(BigInt::from)),
+                _ => None,
+            };
+            let x = parsed.ok_or_else(|| {
+                invalid(format!("witness value {} is not an integer: {}", i, value))
+            })?;
+            if x.magnitude() >= modulus.magnitude() {
+                return Err(invalid(format!(
+                    "witness value {} is {}, not less than the field's modulus {} in absolute \
+                     value",
+                    i, x, modulus
+                )));
+            }
+            let x = if x.sign() == num_bigint::Sign::Minus {
+                x + &modulus
+            } else {
+                x
+            };
+            Ok(Fr::from_str_vartime(&x.to_string()).unwrap())
+        })
+        .collect()
 }
 
 /// load witness from bin file by filename
@@ -158,10 +378,10 @@ pub(crate) fn load_witness_from_bin_reader<Fr: PrimeField, R: Read>(
         bail!("invalid section type");
     }
     let sec_size = reader.read_u64::<LittleEndian>()?;
-    if sec_size != (witness_len * field_size) as u64 {
+    if witness_len.checked_mul(field_size).map(u64::from) != Some(sec_size) {
         bail!("invalid witness section size {}", sec_size);
     }
-    let mut result = Vec::with_capacity(witness_len as usize);
+    let mut result = Vec::with_capacity((witness_len as usize).min(1024));
     for _ in 0..witness_len {
         result.push(read_field::<&mut R, Fr>(&mut reader)?);
     }
@@ -171,93 +391,536 @@ pub(crate) fn load_witness_from_bin_reader<Fr: PrimeField, R: Read>(
 #[cfg(not(target_family = "wasm"))]
 /// load r1cs file by filename with autodetect encoding (bin or json)
 pub fn load_r1cs<G1, G2>(filename: &FileLocation) -> R1CS<<G1 as Group>::Scalar>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+{
+    try_load_r1cs::<G1, G2>(filename).expect("unable to read.")
+}
+
+/// Like `load_r1cs`, but fails with an error instead of panicking if the r1cs can't be read or
+/// fetched, or is truncated, corrupt or compiled for another prime.
+#[cfg(not(target_family = "wasm"))]
+#[tracing::instrument(skip_all)]
+pub fn try_load_r1cs<G1, G2>(
+    filename: &FileLocation,
+) -> Result<R1CS<<G1 as Group>::Scalar>, io::Error>
 where
     G1: Group<Base = <G2 as Group>::Scalar>,
     G2: Group<Base = <G1 as Group>::Scalar>,
 {
     let filename = match filename {
         FileLocation::PathBuf(filename) => filename,
-        FileLocation::URL(_) => panic!("unreachable"),
+        location => return try_load_r1cs_from_bytes::<G1, G2>(&location.read()?),
     };
-    if filename.ends_with("json") {
+    if filename.extension().unwrap_or_default() == "json" {
         load_r1cs_from_json_file(filename)
     } else {
-        load_r1cs_from_bin_file::<G1, G2>(filename)
+        try_load_r1cs_from_bin::<_, G1, G2>(BufReader::new(File::open(filename)?))
+    }
+}
+
+/// load r1cs from the contents of a bin or json file, detected by the bin format's magic. Fails
+/// with an `InvalidData` error on a truncated or corrupt r1cs, or one compiled for another prime.
+pub fn try_load_r1cs_from_bytes<G1, G2>(
+    bytes: &[u8],
+) -> Result<R1CS<<G1 as Group>::Scalar>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+{
+    if bytes.starts_with(b"r1cs") {
+        try_load_r1cs_from_bin::<_, G1, G2>(Cursor::new(bytes))
+    } else {
+        load_r1cs_from_json(bytes)
     }
 }
 
 #[cfg(target_family = "wasm")]
-pub use crate::circom::wasm::load_r1cs;
+pub use crate::circom::wasm::{load_r1cs, try_load_r1cs};
 
-/// load r1cs from json file by filename
-fn load_r1cs_from_json_file<Fr: PrimeField>(filename: &Path) -> R1CS<Fr> {
-    let reader = OpenOptions::new()
-        .read(true)
-        .open(filename)
-        .expect("unable to open.");
-    load_r1cs_from_json(BufReader::new(reader))
+/// The modulus of the field `Fr`.
+pub(crate) fn field_modulus<Fr: PrimeField>() -> BigInt {
+    BigInt::from_str_radix(Fr::MODULUS.trim_start_matches("0x"), 16).unwrap()
 }
 
-/// load r1cs from json by a reader
-fn load_r1cs_from_json<Fr: PrimeField, R: Read>(reader: R) -> R1CS<Fr> {
-    let circuit_json: CircuitJson = serde_json::from_reader(reader).expect("unable to read.");
+/// The prime an r1cs file was compiled for (circom's `--prime`), from the contents of a bin or
+/// json file.
+pub fn read_r1cs_prime(bytes: &[u8]) -> Result<BigInt, io::Error> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    if !bytes.starts_with(b"r1cs") {
+        let circuit_json: CircuitJson = serde_json::from_slice(bytes)?;
+        let prime = circuit_json
+            .prime
+            .ok_or_else(|| invalid("json r1cs has no prime"))?;
+        return prime
+            .parse()
+            .map_err(|_| invalid("invalid prime in json r1cs"));
+    }
+
+    let mut reader = Cursor::new(bytes);
+    reader.set_position(8);
+    let num_sections = reader.read_u32::<LittleEndian>()?;
+    for _ in 0..num_sections {
+        let section_type = reader.read_u32::<LittleEndian>()?;
+        let section_size = reader.read_u64::<LittleEndian>()?;
+        if section_type == 1 {
+            let field_size = reader.read_u32::<LittleEndian>()?;
+            let mut prime = vec![0u8; field_size as usize];
+            reader.read_exact(&mut prime)?;
+            return Ok(BigInt::from_bytes_le(num_bigint::Sign::Plus, &prime));
+        }
+        let next_section = reader
+            .position()
+            .checked_add(section_size)
+            .ok_or_else(|| invalid("r1cs section size overflows"))?;
+        reader.set_position(next_section);
+    }
+    Err(invalid("r1cs has no header section"))
+}
+
+/// load r1cs from json file by filename, in the format of snarkjs's `r1cs export json`
+pub fn load_r1cs_from_json_file<Fr: PrimeField>(filename: &Path) -> Result<R1CS<Fr>, io::Error> {
+    load_r1cs_from_json(BufReader::new(File::open(filename)?))
+}
+
+/// load r1cs from json by a reader, in the format of snarkjs's `r1cs export json`. Coefficients
+/// must be less than the field's prime in absolute value, as for witness values.
+pub fn load_r1cs_from_json<Fr: PrimeField, R: Read>(reader: R) -> Result<R1CS<Fr>, io::Error> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let circuit_json: CircuitJson = serde_json::from_reader(reader)?;
+    let modulus = field_modulus::<Fr>();
+
+    if let Some(prime) = &circuit_json.prime {
+        if prime.parse::<BigInt>().ok() != Some(modulus.clone()) {
+            return Err(invalid(format!(
+                "r1cs is over the prime {}, but the field's modulus is {}",
+                prime, modulus
+            )));
+        }
+    }
 
     let num_inputs = circuit_json.num_inputs + circuit_json.num_outputs + 1;
-    let num_aux = circuit_json.num_variables - num_inputs;
+    let num_aux = circuit_json
+        .num_variables
+        .checked_sub(num_inputs)
+        .ok_or_else(|| {
+            invalid(format!(
+                "r1cs has {} variables, fewer than its {} public signals",
+                circuit_json.num_variables, num_inputs
+            ))
+        })?;
 
-    let convert_constraint = |lc: &BTreeMap<String, String>| {
+    let convert_constraint = |lc: &BTreeMap<usize, String>| {
         lc.iter()
-            .map(|(index, coeff)| (index.parse().unwrap(), Fr::from_str_vartime(coeff).unwrap()))
-            .collect_vec()
+            .map(|(&index, coeff)| {
+                if index >= circuit_json.num_variables {
+                    return Err(invalid(format!(
+                        "constraint uses wire {}, but the r1cs has {} variables",
+                        index, circuit_json.num_variables
+                    )));
+                }
+                match coeff.parse::<BigInt>() {
+                    Ok(x) if x.magnitude() < modulus.magnitude() => {}
+                    Ok(_) => {
+                        return Err(invalid(format!(
+                            "coefficient {} is not less than the field's modulus {} in absolute \
+                             value",
+                            coeff, modulus
+                        )))
+                    }
+                    Err(_) => return Err(invalid(format!("invalid coefficient {:?}", coeff))),
+                }
+                Ok((index, Fr::from_str_vartime(coeff).unwrap()))
+            })
+            .collect::<Result<Vec<_>, io::Error>>()
     };
 
     let constraints = circuit_json
         .constraints
         .iter()
-        .map(|c| {
-            (
-                convert_constraint(&c[0]),
-                convert_constraint(&c[1]),
-                convert_constraint(&c[2]),
-            )
+        .enumerate()
+        .map(|(i, c)| match c.as_slice() {
+            [a, b, c] => Ok((
+                convert_constraint(a)?,
+                convert_constraint(b)?,
+                convert_constraint(c)?,
+            )),
+            _ => Err(invalid(format!(
+                "constraint {} has {} linear combinations instead of 3",
+                i,
+                c.len()
+            ))),
         })
-        .collect_vec();
+        .collect::<Result<Vec<_>, io::Error>>()?;
 
-    R1CS {
+    Ok(R1CS {
         num_inputs,
         num_aux,
         num_variables: circuit_json.num_variables,
+        num_private_inputs: circuit_json.num_private_inputs,
         constraints,
-    }
+    })
 }
 
-/// load r1cs from bin file by filename
-fn load_r1cs_from_bin_file<G1, G2>(filename: &Path) -> R1CS<<G1 as Group>::Scalar>
+/// load r1cs from bin by a reader, checking that it is over `G1`'s scalar field and that its
+/// constraints only use its wires
+fn try_load_r1cs_from_bin<R: Read + Seek, G1, G2>(
+    reader: R,
+) -> Result<R1CS<<G1 as Group>::Scalar>, io::Error>
 where
     G1: Group<Base = <G2 as Group>::Scalar>,
     G2: Group<Base = <G1 as Group>::Scalar>,
 {
-    let reader = OpenOptions::new()
-        .read(true)
-        .open(filename)
-        .expect("unable to open.");
-    load_r1cs_from_bin::<_, G1, G2>(BufReader::new(reader))
-}
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let file = from_reader::<_, G1, G2>(reader)?;
+
+    let prime = BigInt::from_bytes_le(num_bigint::Sign::Plus, &file.header.prime_size);
+    let modulus = field_modulus::<G1::Scalar>();
+    if prime != modulus {
+        return Err(invalid(format!(
+            "r1cs is over the prime {}, but the field's modulus is {}",
+            prime, modulus
+        )));
+    }
 
-/// load r1cs from bin by a reader
-pub(crate) fn load_r1cs_from_bin<R: Read + Seek, G1, G2>(reader: R) -> R1CS<<G1 as Group>::Scalar>
-where
-    G1: Group<Base = <G2 as Group>::Scalar>,
-    G2: Group<Base = <G1 as Group>::Scalar>,
-{
-    let file = from_reader::<_, G1, G2>(reader).expect("unable to read.");
     let num_inputs = (1 + file.header.n_pub_in + file.header.n_pub_out) as usize;
     let num_variables = file.header.n_wires as usize;
-    let num_aux = num_variables - num_inputs;
-    R1CS {
+    let num_aux = num_variables.checked_sub(num_inputs).ok_or_else(|| {
+        invalid(format!(
+            "r1cs has {} variables, fewer than its {} public signals",
+            num_variables, num_inputs
+        ))
+    })?;
+    let wires = file
+        .constraints
+        .iter()
+        .flat_map(|(a, b, c)| a.iter().chain(b).chain(c));
+    if let Some((index, _)) = wires.into_iter().find(|(index, _)| *index >= num_variables) {
+        return Err(invalid(format!(
+            "constraint uses wire {}, but the r1cs has {} variables",
+            index, num_variables
+        )));
+    }
+
+    Ok(R1CS {
         num_aux,
         num_inputs,
         num_variables,
+        num_private_inputs: file.header.n_prv_in as usize,
         constraints: file.constraints,
+    })
+}
+
+mod tests {
+    #[cfg(unix)]
+    #[test]
+    fn witness_generator_failures() {
+        use super::*;
+        use std::os::unix::fs::PermissionsExt;
+
+        type Fr = <pasta_curves::pallas::Point as Group>::Scalar;
+
+        let dir = std::env::temp_dir().join(format!("nova_scotia_reader_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let witness_output = dir.join("witness.wtns");
+        let run = |script: &str, options: &WitnessGeneratorOptions| {
+            let witness_bin = dir.join("witness_generator");
+            fs::write(&witness_bin, format!("#!/bin/sh\n{}\n", script)).unwrap();
+            fs::set_permissions(&witness_bin, fs::Permissions::from_mode(0o755)).unwrap();
+            generate_witness_from_bin::<Fr>(
+                &witness_bin,
+                &"{}".to_string(),
+                &witness_output,
+                options,
+                &CancellationToken::new(),
+            )
+            .unwrap_err()
+        };
+
+        let err = run(
+            "echo 'Error: Assert Failed.' >&2; exit 1",
+            &WitnessGeneratorOptions::default(),
+        );
+        assert!(err.to_string().contains("Error: Assert Failed."));
+
+        let options = WitnessGeneratorOptions {
+            timeout: Some(Duration::from_millis(100)),
+            witness_len: None,
+        };
+        let start = Instant::now();
+        let err = run("sleep 10", &options);
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let err = run("exit 0", &WitnessGeneratorOptions::default());
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        // a witness with one value, while the R1CS expects two
+        let mut wtns = b"wtns".to_vec();
+        for (value, width) in [(2u64, 4), (2, 4), (1, 4), (40, 8), (32, 4)] {
+            wtns.extend_from_slice(&value.to_le_bytes()[..width]);
+        }
+        wtns.extend_from_slice(&[0; 32]);
+        for (value, width) in [(1u64, 4), (2, 4), (32, 8)] {
+            wtns.extend_from_slice(&value.to_le_bytes()[..width]);
+        }
+        wtns.extend_from_slice(&[1; 1]);
+        wtns.extend_from_slice(&[0; 31]);
+        let wtns_file = dir.join("fixture.wtns");
+        fs::write(&wtns_file, wtns).unwrap();
+        let options = WitnessGeneratorOptions {
+            timeout: None,
+            witness_len: Some(2),
+        };
+        let err = run(&format!("cp {} \"$2\"", wtns_file.display()), &options);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("1 values"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn bytes_and_fetched_locations() {
+        use super::*;
+        use crate::fetch::set_url_fetcher;
+        use std::path::PathBuf;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as Group>::Scalar;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta");
+        let from_disk = root.clone();
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let fetched = fetches.clone();
+        set_url_fetcher(move |url| {
+            fetched.fetch_add(1, Ordering::SeqCst);
+            let name = url.strip_prefix("https://circuits.example/").unwrap();
+            fs::read(from_disk.join(name))
+        });
+
+        let r1cs_bytes = fs::read(root.join("toy.r1cs")).unwrap();
+        let wasm_bytes = fs::read(root.join("toy_js/toy.wasm")).unwrap();
+        let locations = [
+            (
+                FileLocation::PathBuf(root.join("toy.r1cs")),
+                FileLocation::PathBuf(root.join("toy_js/toy.wasm")),
+            ),
+            (
+                FileLocation::Bytes(r1cs_bytes.into()),
+                FileLocation::Bytes(wasm_bytes.into()),
+            ),
+            (
+                FileLocation::URL("https://circuits.example/toy.r1cs".to_string()),
+                FileLocation::URL("https://circuits.example/toy_js/toy.wasm".to_string()),
+            ),
+        ];
+
+        let input = r#"{"step_in": ["10", "10"], "adder": "1"}"#.to_string();
+        let loaded = locations
+            .iter()
+            .enumerate()
+            .map(|(i, (r1cs, wasm))| {
+                let r1cs = load_r1cs::<G1, G2>(r1cs);
+                let witness_output = std::env::temp_dir().join(format!(
+                    "nova_scotia_locations_{}_{}.wtns",
+                    std::process::id(),
+                    i
+                ));
+                let witness = generate_witness_from_wasm::<Fr>(
+                    wasm,
+                    &input,
+                    &witness_output,
+                    &WitnessGeneratorOptions::for_r1cs(&r1cs),
+                    &CancellationToken::new(),
+                )
+                .unwrap();
+                fs::remove_file(witness_output).unwrap();
+                (r1cs.num_variables, r1cs.constraints, witness)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(loaded[0].2[1..3], [Fr::from(11), Fr::from(20)]);
+        assert!(loaded.iter().all(|l| *l == loaded[0]));
+
+        // the fetched generator is kept on disk, and reused by the following steps
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        let witness_output = std::env::temp_dir().join(format!(
+            "nova_scotia_locations_{}_2.wtns",
+            std::process::id()
+        ));
+        let input = r#"{"step_in": ["11", "20"], "adder": "2"}"#.to_string();
+        let witness = generate_witness_from_wasm::<Fr>(
+            &locations[2].1,
+            &input,
+            &witness_output,
+            &WitnessGeneratorOptions::default(),
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!(witness[1..3], [Fr::from(13), Fr::from(31)]);
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        fs::remove_file(witness_output).unwrap();
+
+        let prefix = format!("nova_scotia_locations_{}_", std::process::id());
+        for entry in fs::read_dir(std::env::temp_dir()).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy();
+            if name.starts_with(&prefix) && name.ends_with(".generator.wasm") {
+                fs::remove_file(path).unwrap();
+            }
+        }
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn json_r1cs_matches_bin() {
+        use super::*;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta");
+        let bin = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let json = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs.json")));
+        assert_eq!(bin, json);
+        assert_eq!(bin.num_inputs, 5);
+        assert_eq!(bin.constraints.len(), 1);
+
+        // exported for the pasta prime, so not a bn254 r1cs
+        type Bn = <nova_snark::provider::bn256_grumpkin::bn256::Point as Group>::Scalar;
+        let err = load_r1cs_from_json_file::<Bn>(&root.join("toy.r1cs.json")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("r1cs is over the prime"));
+
+        // coefficients of the prime or more are rejected rather than reduced, e.g. p read as 0
+        let json = fs::read_to_string(root.join("toy.r1cs.json")).unwrap();
+        let modulus = field_modulus::<<G1 as Group>::Scalar>();
+        let minus_one = (&modulus - 1u32).to_string();
+        assert!(json.contains(&minus_one));
+        for coeff in [
+            modulus.to_string(),
+            format!("{}0", minus_one),
+            "0x1".to_string(),
+        ] {
+            let json = json.replacen(&minus_one, &coeff, 1);
+            let err = load_r1cs_from_json::<<G1 as Group>::Scalar, _>(json.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        use nova_snark::provider::bn256_grumpkin::{bn256, grumpkin};
+        let bin_path = FileLocation::PathBuf(root.join("toy.r1cs"));
+        let err = try_load_r1cs::<bn256::Point, grumpkin::Point>(&bin_path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        assert_eq!(try_load_r1cs::<G1, G2>(&bin_path).unwrap(), bin);
+        let prime = read_r1cs_prime(&fs::read(root.join("toy.r1cs")).unwrap()).unwrap();
+        assert_eq!(prime, field_modulus::<<G1 as Group>::Scalar>());
+        let mut skipped_section = b"r1cs".to_vec();
+        for word in [1u32, 2, 2] {
+            skipped_section.extend(word.to_le_bytes());
+        }
+        skipped_section.extend(u64::MAX.to_le_bytes());
+        let err = read_r1cs_prime(&skipped_section).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let missing = FileLocation::PathBuf(root.join("missing.r1cs"));
+        let err = try_load_r1cs::<G1, G2>(&missing).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn witness_json_values() {
+        use super::*;
+        use ff::Field;
+
+        type Fr = <pasta_curves::pallas::Point as Group>::Scalar;
+
+        let witness = load_witness_from_json::<Fr, _>(
+            r#"["1", "0x0b", 20, -3, "-0x2", "18446744073709551616"]"#.as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            witness,
+            vec![
+                Fr::ONE,
+                Fr::from(11),
+                Fr::from(20),
+                -Fr::from(3),
+                -Fr::from(2),
+                Fr::from(u64::MAX) + Fr::ONE,
+            ]
+        );
+
+        // values up to the prime in absolute value, on both sides
+        let modulus = field_modulus::<Fr>();
+        let below = &modulus - 1;
+        let witness =
+            load_witness_from_json::<Fr, _>(format!(r#"["{}", "-{}"]"#, below, below).as_bytes())
+                .unwrap();
+        assert_eq!(witness, vec![-Fr::ONE, Fr::ONE]);
+        for value in [modulus.clone(), -modulus] {
+            let err = load_witness_from_json::<Fr, _>(format!(r#"["1", "{}"]"#, value).as_bytes())
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().starts_with("witness value 1 is"));
+        }
+        let err = load_witness_from_json::<Fr, _>(r#"["1", 2.5]"#.as_bytes()).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("witness value 1 is not an integer"));
+
+        let file = std::env::temp_dir().join(format!("nova_scotia_{}.json", std::process::id()));
+        fs::write(&file, r#"["1", "2"]"#).unwrap();
+        assert_eq!(
+            load_witness_from_file::<Fr>(&file).unwrap(),
+            vec![Fr::ONE, Fr::from(2)]
+        );
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn witness_bin_section_sizes() {
+        use super::*;
+
+        type Fr = <pasta_curves::pallas::Point as Group>::Scalar;
+
+        fn wtns(witness_len: u32, sec_size: u64, values: &[[u8; 32]]) -> Vec<u8> {
+            let mut bytes = b"wtns".to_vec();
+            for word in [2u32, 2, 1] {
+                bytes.extend(word.to_le_bytes());
+            }
+            bytes.extend(40u64.to_le_bytes());
+            bytes.extend(32u32.to_le_bytes());
+            bytes.extend([0u8; 32]);
+            bytes.extend(witness_len.to_le_bytes());
+            bytes.extend(2u32.to_le_bytes());
+            bytes.extend(sec_size.to_le_bytes());
+            values.iter().for_each(|value| bytes.extend(value));
+            bytes
+        }
+
+        let mut two = [0u8; 32];
+        two[0] = 2;
+        let witness =
+            load_witness_from_bin_reader::<Fr, _>(wtns(2, 64, &[[0u8; 32], two]).as_slice())
+                .unwrap();
+        assert_eq!(witness, vec![Fr::from(0), Fr::from(2)]);
+
+        // a length whose section size overflows u32, and one claiming far more values than
+        // there are, fail without allocating for them
+        let err =
+            load_witness_from_bin_reader::<Fr, _>(wtns(1 << 30, 0, &[]).as_slice()).unwrap_err();
+        assert!(err.to_string().starts_with("invalid witness section size"));
+        let err = load_witness_from_bin_reader::<Fr, _>(
+            wtns(u32::MAX / 32, (u32::MAX / 32 * 32) as u64, &[two]).as_slice(),
+        )
+        .unwrap_err();
+        assert!(err.downcast_ref::<io::Error>().is_some());
     }
 }
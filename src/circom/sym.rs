@@ -0,0 +1,162 @@
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use ff::PrimeField;
+
+use crate::circom::circuit::R1CS;
+use crate::witness::CircomInput;
+
+/// The names of the input signals of a circuit's main component, read from the `.sym` file
+/// written by `circom --sym`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputSignals {
+    /// Input signal names, without array indices and including `step_in`.
+    pub names: BTreeSet<String>,
+}
+
+impl InputSignals {
+    /// Read the input signals from a `.sym` file. The main component's signals are listed first,
+    /// as its outputs, then public inputs, then private inputs, so `r1cs` tells how many of them
+    /// are inputs.
+    pub fn from_sym<Fr: PrimeField, R: Read>(sym: R, r1cs: &R1CS<Fr>) -> Result<Self, io::Error> {
+        let num_signals = r1cs.num_inputs - 1 + r1cs.num_private_inputs;
+        let mut main_signals = Vec::with_capacity(num_signals);
+        for line in BufReader::new(sym).lines() {
+            let line = line?;
+            let name = line.splitn(4, ',').nth(3).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid .sym line {:?}", line),
+                )
+            })?;
+            match name.strip_prefix("main.") {
+                Some(signal) if !signal.contains('.') => main_signals.push(signal.to_string()),
+                _ => continue,
+            }
+            if main_signals.len() == num_signals {
+                break;
+            }
+        }
+        if main_signals.len() < num_signals {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    ".sym file lists {} signals of main, but the r1cs has {} inputs and outputs",
+                    main_signals.len(),
+                    num_signals
+                ),
+            ));
+        }
+
+        let names = main_signals
+            .iter()
+            .map(|signal| signal.split('[').next().unwrap().to_string())
+            .filter(|name| name != "step_out")
+            .collect();
+        Ok(Self { names })
+    }
+
+    /// Read the input signals from the `.sym` file at `path`, see `from_sym`.
+    pub fn from_sym_file<Fr: PrimeField>(path: &Path, r1cs: &R1CS<Fr>) -> Result<Self, io::Error> {
+        Self::from_sym(File::open(path)?, r1cs)
+    }
+
+    /// Check that `input` sets exactly the circuit's input signals, failing with an
+    /// `InvalidInput` error naming the unknown and missing ones otherwise.
+    pub fn check(&self, input: &CircomInput) -> Result<(), io::Error> {
+        let given = input
+            .extra
+            .keys()
+            .map(String::as_str)
+            .chain(["step_in"])
+            .collect::<BTreeSet<_>>();
+        let declared = self
+            .names
+            .iter()
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>();
+
+        let unknown = given.difference(&declared).collect::<Vec<_>>();
+        let missing = declared.difference(&given).collect::<Vec<_>>();
+        if unknown.is_empty() && missing.is_empty() {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "unknown input signals {:?}, missing input signals {:?}",
+                unknown, missing
+            ),
+        ))
+    }
+}
+
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn typed_inputs_checked_against_sym() {
+        use super::*;
+        use crate::cancel::CancellationToken;
+        use crate::circom::reader::load_r1cs;
+        use crate::witness::{
+            CheckedWitnessGenerator, PrecomputedWitnesses, StepInput, WitnessGenerator,
+        };
+        use crate::{create_public_params, create_recursive_circuit, FileLocation};
+        use nova_snark::traits::Group;
+        use serde::Serialize;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as Group>::Scalar;
+
+        #[derive(Serialize)]
+        struct ToyInput {
+            adder: u64,
+        }
+
+        #[derive(Serialize)]
+        struct Misspelled {
+            addr: u64,
+        }
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let signals = InputSignals::from_sym_file(&root.join("toy.sym"), &r1cs).unwrap();
+        assert_eq!(
+            signals.names,
+            BTreeSet::from(["adder".to_string(), "step_in".to_string()])
+        );
+
+        // (10, 10) -> (11, 20) -> (13, 31)
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+        let recursive_proof = create_recursive_circuit(
+            FileLocation::PathBuf(root.join("toy_js/toy.wasm")),
+            r1cs,
+            vec![ToyInput { adder: 1 }, ToyInput { adder: 2 }],
+            z0.clone(),
+            &pp,
+        )
+        .unwrap();
+        let z_n = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(z_n, vec![Fr::from(13), Fr::from(31)]);
+
+        // the wrapped generator would fail with UnexpectedEof if it ran
+        let witness_generator = CheckedWitnessGenerator {
+            signals,
+            witness_generator: PrecomputedWitnesses::<Fr>::new(vec![]),
+        };
+        let input = CircomInput::new(&z0, Misspelled { addr: 1 }.to_signals().unwrap());
+        let err = witness_generator
+            .generate(&input, &CancellationToken::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            r#"unknown input signals ["addr"], missing input signals ["adder"]"#
+        );
+    }
+}
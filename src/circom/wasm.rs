@@ -1,10 +1,10 @@
 use crate::{FileLocation, R1CS};
 
-use crate::circom::reader::{load_r1cs_from_bin, load_witness_from_bin_reader};
+use crate::circom::reader::{load_witness_from_bin_reader, try_load_r1cs_from_bytes};
 use ff::PrimeField;
 use js_sys::Uint8Array;
 use nova_snark::traits::Group;
-use std::io::Cursor;
+use std::io::{self, Cursor};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 
@@ -12,6 +12,7 @@ use wasm_bindgen_futures::JsFuture;
 extern "C" {
     fn read_file_async(path: &str) -> JsValue;
     fn generate_witness_browser_async(input_json_string: &str, wasm_file: &str) -> JsValue;
+    fn generate_witness_browser_bytes_async(input_json_string: &str, wasm_bytes: &[u8]) -> JsValue;
 }
 
 #[wasm_bindgen]
@@ -40,33 +41,85 @@ pub async fn generate_witness_browser(input_json_string: &str, wasm_file: &str)
     }
 }
 
+#[wasm_bindgen]
+pub async fn generate_witness_browser_bytes(
+    input_json_string: &str,
+    wasm_bytes: &[u8],
+) -> Uint8Array {
+    let promise_as_js_value = generate_witness_browser_bytes_async(input_json_string, wasm_bytes);
+    let promise = js_sys::Promise::from(promise_as_js_value);
+    let future = JsFuture::from(promise);
+    let result: Result<JsValue, JsValue> = future.await;
+    if let Ok(content) = result {
+        return Uint8Array::new(&content);
+    } else {
+        return Uint8Array::new(&JsValue::NULL);
+    }
+}
+
 #[cfg(target_family = "wasm")]
 /// load r1cs file by filename with autodetect encoding (bin or json)
+#[tracing::instrument(skip_all)]
 pub async fn load_r1cs<G1, G2>(filename: &FileLocation) -> R1CS<<G1 as Group>::Scalar>
 where
     G1: Group<Base = <G2 as Group>::Scalar>,
     G2: Group<Base = <G1 as Group>::Scalar>,
 {
-    let filename = match filename {
-        FileLocation::PathBuf(_) => panic!("unreachable"),
-        FileLocation::URL(path) => path,
+    try_load_r1cs::<G1, G2>(filename)
+        .await
+        .expect("unable to read.")
+}
+
+#[cfg(target_family = "wasm")]
+/// Like `load_r1cs`, but fails with an error instead of panicking if the r1cs is a path, or is
+/// truncated, corrupt or compiled for another prime.
+pub async fn try_load_r1cs<G1, G2>(
+    filename: &FileLocation,
+) -> Result<R1CS<<G1 as Group>::Scalar>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+{
+    let r1cs_ser = match filename {
+        FileLocation::PathBuf(path) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "can't read {} in the browser, pass a URL or bytes",
+                    path.display()
+                ),
+            ))
+        }
+        FileLocation::URL(path) => read_file(path).await.to_vec(),
+        FileLocation::Bytes(bytes) => bytes.to_vec(),
     };
-    let r1cs_ser = read_file(filename).await.to_vec();
-    let r1cs_cursor = Cursor::new(r1cs_ser);
-    load_r1cs_from_bin::<_, G1, G2>(r1cs_cursor)
+    try_load_r1cs_from_bytes::<G1, G2>(&r1cs_ser)
 }
 
 #[cfg(target_family = "wasm")]
+/// Run a Circom `.wasm` witness generator served at a URL or in memory, failing if it is a path
+/// or if the generator doesn't return a valid witness.
 pub async fn generate_witness_from_wasm<Fr: PrimeField>(
     witness_wasm: &FileLocation,
     witness_input_json: &String,
-) -> Vec<Fr> {
-    let witness_wasm = match witness_wasm {
-        FileLocation::PathBuf(_) => panic!("unreachable"),
-        FileLocation::URL(path) => path,
+) -> Result<Vec<Fr>, io::Error> {
+    let witness_output = match witness_wasm {
+        FileLocation::PathBuf(path) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "can't run {} in the browser, pass a URL or bytes",
+                    path.display()
+                ),
+            ))
+        }
+        FileLocation::URL(path) => generate_witness_browser(witness_input_json, path).await,
+        FileLocation::Bytes(bytes) => {
+            generate_witness_browser_bytes(witness_input_json, bytes).await
+        }
     };
-    let witness_output = generate_witness_browser(witness_input_json, witness_wasm).await;
     let witness_output = witness_output.to_vec();
     let witness_output = Cursor::new(witness_output);
-    load_witness_from_bin_reader(witness_output).expect("read witness failed")
+    load_witness_from_bin_reader(witness_output)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
 }
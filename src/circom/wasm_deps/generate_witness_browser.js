@@ -10,9 +10,16 @@ export async function generate_witness_browser_async(
   input_json_string,
   wasm_file
 ) {
-  const input = JSON.parse(input_json_string);
   const buffer = await read_file_async(wasm_file);
-  const witnessCalculator = await wc(buffer);
+  return generate_witness_browser_bytes_async(input_json_string, buffer);
+}
+
+export async function generate_witness_browser_bytes_async(
+  input_json_string,
+  wasm_bytes
+) {
+  const input = JSON.parse(input_json_string);
+  const witnessCalculator = await wc(wasm_bytes);
   const buff = await witnessCalculator.calculateWTNSBin(input, 0);
   return buff;
 }
@@ -0,0 +1,597 @@
+use std::collections::HashMap;
+use std::io;
+#[cfg(not(target_family = "wasm"))]
+use std::path::Path;
+
+use bellpepper::util_cs::witness_cs::WitnessCS;
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use nova_snark::traits::circuit::{StepCircuit, TrivialCircuit};
+use serde_json::Value;
+
+use crate::cancel::CancellationToken;
+use crate::circom::circuit::{CircomCircuit, R1CS};
+#[cfg(not(target_family = "wasm"))]
+use crate::prove::block_on;
+use crate::prove::compute_witness;
+use crate::witness::{file_witness_generator, WitnessGenerator};
+use crate::FileLocation;
+
+/// One stage of a `ComposedCircuit`.
+#[derive(Clone)]
+pub enum Stage<Fr: PrimeField, G: StepCircuit<Fr>> {
+    /// A Circom step circuit, along with the witness generator used to fill in its witness.
+    Circom {
+        circuit: CircomCircuit<Fr>,
+        witness_generator_file: FileLocation,
+    },
+    /// A hand-written bellpepper gadget.
+    Gadget(G),
+}
+
+/// A step circuit made of a sequence of stages, where the outputs of each stage are wired into
+/// the inputs of the next one. The inputs of the first stage are the step's `z_i`, and the
+/// outputs of the last stage are its `z_{i+1}`. Build one with `ComposedCircuit::builder()`.
+///
+/// Gadgets are any `StepCircuit` over the same field. Like Circom stages, a gadget returns as
+/// many values as its `arity`, and that arity must match the stages around it. Every gadget of a
+/// circuit has the same type `G`, so if you need several kinds of gadgets in the same circuit,
+/// wrap them in an enum. Closures can't be gadgets: `StepCircuit::synthesize` is generic over
+/// the constraint system, which a closure (or a `Box<dyn Fn>`) can't be.
+#[derive(Clone)]
+pub struct ComposedCircuit<Fr: PrimeField, G: StepCircuit<Fr>> {
+    pub(crate) stages: Vec<Stage<Fr, G>>,
+}
+
+/// Builder of a `ComposedCircuit`, appending one stage at a time.
+#[derive(Clone)]
+pub struct ComposedCircuitBuilder<Fr: PrimeField, G: StepCircuit<Fr>> {
+    stages: Vec<Stage<Fr, G>>,
+}
+
+impl<Fr: PrimeField, G: StepCircuit<Fr>> ComposedCircuitBuilder<Fr, G> {
+    /// Append a Circom stage, whose `step_in` is wired to the outputs of the previous stage.
+    pub fn circom(mut self, r1cs: R1CS<Fr>, witness_generator_file: FileLocation) -> Self {
+        self.stages.push(Stage::Circom {
+            circuit: CircomCircuit {
+                r1cs,
+                witness: None,
+            },
+            witness_generator_file,
+        });
+        self
+    }
+
+    /// Append a gadget stage, which receives the outputs of the previous stage.
+    pub fn gadget(mut self, gadget: G) -> Self {
+        self.stages.push(Stage::Gadget(gadget));
+        self
+    }
+
+    /// The composed circuit. Fails if there are no stages, or if a stage's arity differs from
+    /// the number of outputs of the stage before it.
+    pub fn build(self) -> Result<ComposedCircuit<Fr, G>, io::Error> {
+        let first = self.stages.first().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a composed circuit needs at least one stage",
+            )
+        })?;
+
+        let mut outputs = first.arity();
+        for (i, stage) in self.stages.iter().enumerate().skip(1) {
+            if stage.arity() != outputs {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "stage {} takes {} inputs, but stage {} returns {} outputs",
+                        i,
+                        stage.arity(),
+                        i - 1,
+                        outputs
+                    ),
+                ));
+            }
+            outputs = stage.arity();
+        }
+
+        Ok(ComposedCircuit {
+            stages: self.stages,
+        })
+    }
+}
+
+impl<Fr: PrimeField, G: StepCircuit<Fr>> ComposedCircuit<Fr, G> {
+    pub fn builder() -> ComposedCircuitBuilder<Fr, G> {
+        ComposedCircuitBuilder { stages: vec![] }
+    }
+
+    /// The step's `z_{i+1}` for input `z`, running the stages in order. Circom stages return
+    /// the outputs of their witness, so call this on a circuit returned by `with_witness`.
+    pub fn output(&self, z: &[Fr]) -> Result<Vec<Fr>, SynthesisError> {
+        self.stages
+            .iter()
+            .try_fold(z.to_vec(), |z, stage| stage.output(&z))
+    }
+
+    /// Number of Circom stages, i.e. the number of private inputs expected per step.
+    pub fn circom_stage_count(&self) -> usize {
+        self.stages
+            .iter()
+            .filter(|stage| matches!(stage, Stage::Circom { .. }))
+            .count()
+    }
+
+    /// Compute the witness of every Circom stage for a step with input `z`, running the stages
+    /// in order so that each Circom stage sees the outputs of the previous stages as `step_in`.
+    /// `private_inputs` holds one input per Circom stage, and `step` is only used in errors.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn with_witness(
+        &self,
+        step: usize,
+        z: &[Fr],
+        private_inputs: Vec<HashMap<String, Value>>,
+        witness_generator_output: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<Self, io::Error> {
+        let witness_generator = |file: &_| {
+            Ok(file_witness_generator(
+                file,
+                witness_generator_output.to_path_buf(),
+            ))
+        };
+        block_on(self.witness_stages(step, z, private_inputs, witness_generator, cancel))
+    }
+
+    /// Compute the witness of every Circom stage for a step with input `z`, running the stages
+    /// in order so that each Circom stage sees the outputs of the previous stages as `step_in`.
+    /// `private_inputs` holds one input per Circom stage, and `step` is only used in errors.
+    #[cfg(target_family = "wasm")]
+    pub async fn with_witness(
+        &self,
+        step: usize,
+        z: &[Fr],
+        private_inputs: Vec<HashMap<String, Value>>,
+        cancel: &CancellationToken,
+    ) -> Result<Self, io::Error> {
+        self.witness_stages(step, z, private_inputs, file_witness_generator, cancel)
+            .await
+    }
+
+    /// `with_witness`, running each Circom stage's witness generator file with the generator
+    /// returned by `witness_generator`.
+    pub(crate) async fn witness_stages<W: WitnessGenerator<Fr>>(
+        &self,
+        step: usize,
+        z: &[Fr],
+        private_inputs: Vec<HashMap<String, Value>>,
+        witness_generator: impl Fn(&FileLocation) -> Result<W, io::Error>,
+        cancel: &CancellationToken,
+    ) -> Result<Self, io::Error> {
+        if private_inputs.len() != self.circom_stage_count() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "step {} has {} private inputs, but the circuit has {} Circom stages",
+                    step,
+                    private_inputs.len(),
+                    self.circom_stage_count()
+                ),
+            ));
+        }
+
+        let mut private_inputs = private_inputs.into_iter();
+        let mut z = z.to_vec();
+        let mut stages = Vec::with_capacity(self.stages.len());
+        for stage in &self.stages {
+            let stage = match stage {
+                Stage::Circom {
+                    circuit,
+                    witness_generator_file,
+                } => {
+                    let witness = compute_witness(
+                        &witness_generator(witness_generator_file)?,
+                        step,
+                        &z,
+                        private_inputs.next().unwrap(),
+                        &circuit.r1cs,
+                        cancel,
+                    )
+                    .await?;
+                    Stage::Circom {
+                        circuit: CircomCircuit {
+                            r1cs: circuit.r1cs.clone(),
+                            witness: Some(witness),
+                        },
+                        witness_generator_file: witness_generator_file.clone(),
+                    }
+                }
+                Stage::Gadget(gadget) => Stage::Gadget(gadget.clone()),
+            };
+            z = stage.output(&z).map_err(|err| {
+                io::Error::other(format!(
+                    "stage {} of step {} failed: {}",
+                    stages.len(),
+                    step,
+                    err
+                ))
+            })?;
+            if z.len() != stage.arity() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "stage {} of step {} returned {} outputs, expected {}",
+                        stages.len(),
+                        step,
+                        z.len(),
+                        stage.arity()
+                    ),
+                ));
+            }
+            stages.push(stage);
+        }
+
+        Ok(Self { stages })
+    }
+}
+
+/// A step circuit made of chained copies of a single Circom step circuit, see
+/// `ComposedCircuit::packed`.
+pub type PackedCircuit<Fr> = ComposedCircuit<Fr, TrivialCircuit<Fr>>;
+
+impl<Fr: PrimeField> PackedCircuit<Fr> {
+    /// Pack `packing` invocations of a single Circom step circuit into one Nova step, where the
+    /// `step_out` of each copy is the `step_in` of the next. The witness generator runs once per
+    /// copy, so the packing factor can be tuned without recompiling the circuit. Fails if
+    /// `packing` is 0.
+    pub fn packed(
+        r1cs: R1CS<Fr>,
+        witness_generator_file: FileLocation,
+        packing: usize,
+    ) -> Result<Self, io::Error> {
+        // copies of the same circuit have the same arity, so this only fails without copies
+        (0..packing)
+            .fold(Self::builder(), |circuit, _| {
+                circuit.circom(r1cs.clone(), witness_generator_file.clone())
+            })
+            .build()
+    }
+}
+
+/// Group the per-invocation private inputs of a packed circuit into per-step private inputs,
+/// `packing` at a time. Fails if `packing` is 0 or doesn't divide the number of inputs, since
+/// every step runs all `packing` copies of the circuit.
+pub fn pack_private_inputs(
+    private_inputs: Vec<HashMap<String, Value>>,
+    packing: usize,
+) -> Result<Vec<Vec<HashMap<String, Value>>>, io::Error> {
+    if private_inputs.len().checked_rem(packing) != Some(0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} private inputs can't be packed {} per step",
+                private_inputs.len(),
+                packing
+            ),
+        ));
+    }
+
+    Ok(private_inputs
+        .chunks(packing)
+        .map(|chunk| chunk.to_vec())
+        .collect())
+}
+
+impl<Fr: PrimeField, G: StepCircuit<Fr>> Stage<Fr, G> {
+    fn arity(&self) -> usize {
+        match self {
+            Stage::Circom { circuit, .. } => circuit.arity(),
+            Stage::Gadget(gadget) => gadget.arity(),
+        }
+    }
+
+    fn output(&self, z: &[Fr]) -> Result<Vec<Fr>, SynthesisError> {
+        match self {
+            Stage::Circom { circuit, .. } => Ok(circuit.get_public_outputs()),
+            Stage::Gadget(gadget) => gadget_output(gadget, z),
+        }
+    }
+}
+
+impl<Fr: PrimeField, G: StepCircuit<Fr>> StepCircuit<Fr> for ComposedCircuit<Fr, G> {
+    fn arity(&self) -> usize {
+        self.stages[0].arity()
+    }
+
+    fn synthesize<CS: ConstraintSystem<Fr>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<Fr>],
+    ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+        let mut z = z.to_vec();
+        for (i, stage) in self.stages.iter().enumerate() {
+            let mut cs = cs.namespace(|| format!("stage {}", i));
+            z = match stage {
+                Stage::Circom { circuit, .. } => circuit.vanilla_synthesize(&mut cs, &z)?,
+                Stage::Gadget(gadget) => gadget.synthesize(&mut cs, &z)?,
+            };
+            if z.len() != stage.arity() {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+        }
+
+        Ok(z)
+    }
+}
+
+/// The values a gadget returns for input `z`, found by synthesizing it without constraints.
+fn gadget_output<Fr: PrimeField, G: StepCircuit<Fr>>(
+    gadget: &G,
+    z: &[Fr],
+) -> Result<Vec<Fr>, SynthesisError> {
+    let mut cs = WitnessCS::<Fr>::new();
+    let z = z
+        .iter()
+        .enumerate()
+        .map(|(i, x)| AllocatedNum::alloc(cs.namespace(|| format!("z{}", i)), || Ok(*x)))
+        .collect::<Result<Vec<_>, _>>()?;
+    gadget
+        .synthesize(&mut cs, &z)?
+        .iter()
+        .map(|x| x.get_value().ok_or(SynthesisError::AssignmentMissing))
+        .collect()
+}
+
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn circom_and_gadget_stages() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use bellpepper_core::test_cs::TestConstraintSystem;
+        use std::env::temp_dir;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as nova_snark::traits::Group>::Scalar;
+
+        // doubles each value of its input
+        #[derive(Clone)]
+        struct Double;
+
+        impl StepCircuit<Fr> for Double {
+            fn arity(&self) -> usize {
+                2
+            }
+
+            fn synthesize<CS: ConstraintSystem<Fr>>(
+                &self,
+                cs: &mut CS,
+                z: &[AllocatedNum<Fr>],
+            ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+                let mut z_out = vec![];
+                for (i, x) in z.iter().enumerate() {
+                    let y = AllocatedNum::alloc(cs.namespace(|| format!("double {}", i)), || {
+                        x.get_value()
+                            .map(|x| x.double())
+                            .ok_or(SynthesisError::AssignmentMissing)
+                    })?;
+                    cs.enforce(
+                        || format!("double {} constraint", i),
+                        |lc| lc + x.get_variable() + x.get_variable(),
+                        |lc| lc + CS::one(),
+                        |lc| lc + y.get_variable(),
+                    );
+                    z_out.push(y);
+                }
+                Ok(z_out)
+            }
+        }
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            root.join("examples/toy/pasta/toy.r1cs"),
+        ));
+        let witness_generator_file =
+            FileLocation::PathBuf(root.join("examples/toy/pasta/toy_js/toy.wasm"));
+
+        let circuit = ComposedCircuit::builder()
+            .circom(r1cs.clone(), witness_generator_file.clone())
+            .gadget(Double)
+            .circom(r1cs, witness_generator_file)
+            .build()
+            .unwrap();
+        assert_eq!(circuit.arity(), 2);
+        assert_eq!(circuit.circom_stage_count(), 2);
+
+        let mut private_inputs = Vec::new();
+        for adder in [1, 2] {
+            let mut private_input = HashMap::new();
+            private_input.insert("adder".to_string(), serde_json::json!(adder));
+            private_inputs.push(private_input);
+        }
+
+        let witness_generator_output = temp_dir().join("nova_scotia_compose_test.wtns");
+        let z0 = [Fr::from(10), Fr::from(10)];
+        let circuit = circuit
+            .with_witness(
+                0,
+                &z0,
+                private_inputs,
+                &witness_generator_output,
+                &CancellationToken::new(),
+            )
+            .unwrap();
+
+        // (10, 10) -> (11, 20) -> (22, 40) -> (24, 62)
+        let z_out = circuit.output(&z0).unwrap();
+        assert_eq!(z_out, vec![Fr::from(24), Fr::from(62)]);
+
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let z = z0
+            .iter()
+            .enumerate()
+            .map(|(i, x)| AllocatedNum::alloc(cs.namespace(|| format!("z{}", i)), || Ok(*x)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let z_out_allocated = circuit.synthesize(&mut cs, &z).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(
+            z_out_allocated
+                .iter()
+                .map(|x| x.get_value().unwrap())
+                .collect::<Vec<_>>(),
+            z_out
+        );
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn packed_circom_stages() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use bellpepper_core::test_cs::TestConstraintSystem;
+        use std::env::temp_dir;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as nova_snark::traits::Group>::Scalar;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            root.join("examples/toy/pasta/toy.r1cs"),
+        ));
+        let witness_generator_file =
+            FileLocation::PathBuf(root.join("examples/toy/pasta/toy_js/toy.wasm"));
+
+        let err = PackedCircuit::packed(r1cs.clone(), witness_generator_file.clone(), 0)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let circuit = PackedCircuit::packed(r1cs, witness_generator_file, 3).unwrap();
+        assert_eq!(circuit.arity(), 2);
+        assert_eq!(circuit.circom_stage_count(), 3);
+
+        let mut private_inputs = Vec::new();
+        for adder in 0..6 {
+            let mut private_input = HashMap::new();
+            private_input.insert("adder".to_string(), serde_json::json!(adder));
+            private_inputs.push(private_input);
+        }
+        let err = pack_private_inputs(private_inputs[1..].to_vec(), 3).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let private_inputs = pack_private_inputs(private_inputs, 3).unwrap();
+        assert_eq!(private_inputs.len(), 2);
+
+        let witness_generator_output = temp_dir().join("nova_scotia_packed_test.wtns");
+        let z0 = [Fr::from(10), Fr::from(10)];
+        let circuit = circuit
+            .with_witness(
+                0,
+                &z0,
+                private_inputs[1].clone(),
+                &witness_generator_output,
+                &CancellationToken::new(),
+            )
+            .unwrap();
+
+        // (10, 10) -> (13, 20) -> (17, 33) -> (22, 50)
+        let z_out = circuit.output(&z0).unwrap();
+        assert_eq!(z_out, vec![Fr::from(22), Fr::from(50)]);
+
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let z = z0
+            .iter()
+            .enumerate()
+            .map(|(i, x)| AllocatedNum::alloc(cs.namespace(|| format!("z{}", i)), || Ok(*x)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        circuit.synthesize(&mut cs, &z).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn reject_empty_stages() {
+        use super::*;
+
+        type G1 = pasta_curves::pallas::Point;
+        type Fr = <G1 as nova_snark::traits::Group>::Scalar;
+
+        let err = ComposedCircuit::<Fr, TrivialCircuit<Fr>>::builder()
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn reject_mismatched_arities() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as nova_snark::traits::Group>::Scalar;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            root.join("examples/toy/pasta/toy.r1cs"),
+        ));
+        let witness_generator_file =
+            FileLocation::PathBuf(root.join("examples/toy/pasta/toy_js/toy.wasm"));
+
+        // the toy circuit returns 2 outputs, but the trivial circuit takes 1 input
+        let err = ComposedCircuit::builder()
+            .circom(r1cs, witness_generator_file)
+            .gadget(TrivialCircuit::<Fr>::default())
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            "stage 1 takes 1 inputs, but stage 0 returns 2 outputs"
+        );
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn reject_wrong_private_input_count() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use std::env::temp_dir;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as nova_snark::traits::Group>::Scalar;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            root.join("examples/toy/pasta/toy.r1cs"),
+        ));
+        let witness_generator_file =
+            FileLocation::PathBuf(root.join("examples/toy/pasta/toy_js/toy.wasm"));
+        let circuit = PackedCircuit::packed(r1cs, witness_generator_file, 2).unwrap();
+
+        let err = circuit
+            .with_witness(
+                3,
+                &[Fr::from(10), Fr::from(10)],
+                vec![HashMap::new()],
+                &temp_dir().join("nova_scotia_wrong_count_test.wtns"),
+                &CancellationToken::new(),
+            )
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            "step 3 has 1 private inputs, but the circuit has 2 Circom stages"
+        );
+    }
+}
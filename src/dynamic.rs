@@ -0,0 +1,402 @@
+//! Proving with the curve cycle picked at runtime from the prime an R1CS was compiled for, for
+//! tools that handle any supported circuit without choosing `G1`/`G2` at compile time.
+
+use std::io;
+
+use ff::PrimeField;
+use nova_snark::{errors::NovaError, provider, PublicParams};
+use num_bigint::BigInt;
+use num_traits::Num;
+
+use crate::circom::circuit::R1CS;
+use crate::circom::reader::{field_modulus, read_r1cs_prime, try_load_r1cs_from_bytes};
+use crate::proof::RecursiveProof;
+#[cfg(not(target_family = "wasm"))]
+use crate::witness::StepInput;
+use crate::{create_public_params, to_hex_strings, C1, C2, F};
+#[cfg(not(target_family = "wasm"))]
+use crate::{create_recursive_circuit, FileLocation};
+
+type Bn256 = provider::bn256_grumpkin::bn256::Point;
+type Grumpkin = provider::bn256_grumpkin::grumpkin::Point;
+type Pallas = pasta_curves::pallas::Point;
+type Vesta = pasta_curves::vesta::Point;
+type Secq256k1 = provider::secp_secq::secq256k1::Point;
+type Secp256k1 = provider::secp_secq::secp256k1::Point;
+
+/// A cycle of curves supported by Nova Scotia, named by its primary curve first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveCycle {
+    /// For circuits compiled with `--prime bn128`.
+    Bn256Grumpkin,
+    /// For circuits compiled with `--prime vesta`.
+    PallasVesta,
+    /// For circuits compiled with `--prime secq256k1`, whose field is secp256k1's base field.
+    Secq256k1Secp256k1,
+}
+
+impl CurveCycle {
+    /// The cycle whose primary scalar field has modulus `prime`.
+    pub fn from_prime(prime: &BigInt) -> Option<Self> {
+        if *prime == field_modulus::<F<Bn256>>() {
+            Some(CurveCycle::Bn256Grumpkin)
+        } else if *prime == field_modulus::<F<Pallas>>() {
+            Some(CurveCycle::PallasVesta)
+        } else if *prime == field_modulus::<F<Secq256k1>>() {
+            Some(CurveCycle::Secq256k1Secp256k1)
+        } else {
+            None
+        }
+    }
+}
+
+/// An R1CS over the primary scalar field of one of the supported cycles.
+#[derive(Clone, Debug)]
+pub enum DynR1CS {
+    Bn256Grumpkin(R1CS<F<Bn256>>),
+    PallasVesta(R1CS<F<Pallas>>),
+    Secq256k1Secp256k1(R1CS<F<Secq256k1>>),
+}
+
+/// Public params for one of the supported cycles.
+pub enum DynPublicParams {
+    Bn256Grumpkin(PublicParams<Bn256, Grumpkin, C1<Bn256>, C2<Grumpkin>>),
+    PallasVesta(PublicParams<Pallas, Vesta, C1<Pallas>, C2<Vesta>>),
+    Secq256k1Secp256k1(PublicParams<Secq256k1, Secp256k1, C1<Secq256k1>, C2<Secp256k1>>),
+}
+
+/// A `RecursiveProof` over one of the supported cycles.
+pub enum DynRecursiveProof {
+    Bn256Grumpkin(RecursiveProof<Bn256, Grumpkin, C1<Bn256>, C2<Grumpkin>>),
+    PallasVesta(RecursiveProof<Pallas, Vesta, C1<Pallas>, C2<Vesta>>),
+    Secq256k1Secp256k1(RecursiveProof<Secq256k1, Secp256k1, C1<Secq256k1>, C2<Secp256k1>>),
+}
+
+impl DynR1CS {
+    /// Load an r1cs file (bin or json), picking the curve cycle from its prime.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn load(r1cs: &FileLocation) -> Result<Self, io::Error> {
+        Self::from_bytes(&r1cs.read()?)
+    }
+
+    /// Load an r1cs from the contents of a bin or json file, picking the curve cycle from its
+    /// prime.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let prime = read_r1cs_prime(bytes)?;
+        match CurveCycle::from_prime(&prime) {
+            Some(CurveCycle::Bn256Grumpkin) => {
+                let r1cs = try_load_r1cs_from_bytes::<Bn256, Grumpkin>(bytes)?;
+                Ok(DynR1CS::Bn256Grumpkin(r1cs))
+            }
+            Some(CurveCycle::PallasVesta) => {
+                let r1cs = try_load_r1cs_from_bytes::<Pallas, Vesta>(bytes)?;
+                Ok(DynR1CS::PallasVesta(r1cs))
+            }
+            Some(CurveCycle::Secq256k1Secp256k1) => {
+                let r1cs = try_load_r1cs_from_bytes::<Secq256k1, Secp256k1>(bytes)?;
+                Ok(DynR1CS::Secq256k1Secp256k1(r1cs))
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "r1cs prime {} is not supported, compile with --prime bn128, --prime vesta \
+                     or --prime secq256k1",
+                    prime
+                ),
+            )),
+        }
+    }
+
+    pub fn curve_cycle(&self) -> CurveCycle {
+        match self {
+            DynR1CS::Bn256Grumpkin(_) => CurveCycle::Bn256Grumpkin,
+            DynR1CS::PallasVesta(_) => CurveCycle::PallasVesta,
+            DynR1CS::Secq256k1Secp256k1(_) => CurveCycle::Secq256k1Secp256k1,
+        }
+    }
+
+    pub fn create_public_params(&self) -> DynPublicParams {
+        match self {
+            DynR1CS::Bn256Grumpkin(r1cs) => {
+                DynPublicParams::Bn256Grumpkin(create_public_params(r1cs.clone()))
+            }
+            DynR1CS::PallasVesta(r1cs) => {
+                DynPublicParams::PallasVesta(create_public_params(r1cs.clone()))
+            }
+            DynR1CS::Secq256k1Secp256k1(r1cs) => {
+                DynPublicParams::Secq256k1Secp256k1(create_public_params(r1cs.clone()))
+            }
+        }
+    }
+}
+
+impl DynPublicParams {
+    pub fn curve_cycle(&self) -> CurveCycle {
+        match self {
+            DynPublicParams::Bn256Grumpkin(_) => CurveCycle::Bn256Grumpkin,
+            DynPublicParams::PallasVesta(_) => CurveCycle::PallasVesta,
+            DynPublicParams::Secq256k1Secp256k1(_) => CurveCycle::Secq256k1Secp256k1,
+        }
+    }
+}
+
+impl DynRecursiveProof {
+    pub fn curve_cycle(&self) -> CurveCycle {
+        match self {
+            DynRecursiveProof::Bn256Grumpkin(_) => CurveCycle::Bn256Grumpkin,
+            DynRecursiveProof::PallasVesta(_) => CurveCycle::PallasVesta,
+            DynRecursiveProof::Secq256k1Secp256k1(_) => CurveCycle::Secq256k1Secp256k1,
+        }
+    }
+
+    /// Verify every folded step, returning `z_n`.
+    pub fn verify(&self, pp: &DynPublicParams) -> Result<Vec<BigInt>, io::Error> {
+        match (self, pp) {
+            (DynRecursiveProof::Bn256Grumpkin(proof), DynPublicParams::Bn256Grumpkin(pp)) => {
+                let z_n = proof.verify(pp).map_err(verification_error)?;
+                Ok(from_field_elements(&z_n))
+            }
+            (DynRecursiveProof::PallasVesta(proof), DynPublicParams::PallasVesta(pp)) => {
+                let z_n = proof.verify(pp).map_err(verification_error)?;
+                Ok(from_field_elements(&z_n))
+            }
+            (
+                DynRecursiveProof::Secq256k1Secp256k1(proof),
+                DynPublicParams::Secq256k1Secp256k1(pp),
+            ) => {
+                let z_n = proof.verify(pp).map_err(verification_error)?;
+                Ok(from_field_elements(&z_n))
+            }
+            _ => Err(cycle_mismatch(
+                "recursive proof",
+                self.curve_cycle(),
+                pp.curve_cycle(),
+            )),
+        }
+    }
+}
+
+/// `create_recursive_circuit` on whichever cycle `r1cs` and `pp` are over, with
+/// `start_public_input` as integers in the circuit's field.
+#[cfg(not(target_family = "wasm"))]
+pub fn create_recursive_circuit_dyn<I: StepInput>(
+    witness_generator_file: FileLocation,
+    r1cs: DynR1CS,
+    private_inputs: Vec<I>,
+    start_public_input: &[BigInt],
+    pp: &DynPublicParams,
+) -> Result<DynRecursiveProof, io::Error> {
+    match (r1cs, pp) {
+        (DynR1CS::Bn256Grumpkin(r1cs), DynPublicParams::Bn256Grumpkin(pp)) => {
+            create_recursive_circuit(
+                witness_generator_file,
+                r1cs,
+                private_inputs,
+                to_field_elements(start_public_input)?,
+                pp,
+            )
+            .map(DynRecursiveProof::Bn256Grumpkin)
+        }
+        (DynR1CS::PallasVesta(r1cs), DynPublicParams::PallasVesta(pp)) => create_recursive_circuit(
+            witness_generator_file,
+            r1cs,
+            private_inputs,
+            to_field_elements(start_public_input)?,
+            pp,
+        )
+        .map(DynRecursiveProof::PallasVesta),
+        (DynR1CS::Secq256k1Secp256k1(r1cs), DynPublicParams::Secq256k1Secp256k1(pp)) => {
+            create_recursive_circuit(
+                witness_generator_file,
+                r1cs,
+                private_inputs,
+                to_field_elements(start_public_input)?,
+                pp,
+            )
+            .map(DynRecursiveProof::Secq256k1Secp256k1)
+        }
+        (r1cs, pp) => Err(cycle_mismatch("r1cs", r1cs.curve_cycle(), pp.curve_cycle())),
+    }
+}
+
+fn cycle_mismatch(what: &str, cycle: CurveCycle, pp_cycle: CurveCycle) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "{} is over {:?}, but the public params are for {:?}",
+            what, cycle, pp_cycle
+        ),
+    )
+}
+
+fn verification_error(err: NovaError) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("verification failed: {:?}", err),
+    )
+}
+
+/// Integers as elements of `Fr`, with negative values taken modulo its prime.
+#[cfg(not(target_family = "wasm"))]
+fn to_field_elements<Fr: PrimeField>(values: &[BigInt]) -> Result<Vec<Fr>, io::Error> {
+    let modulus = field_modulus::<Fr>();
+    values
+        .iter()
+        .map(|x| {
+            if *x >= modulus {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not less than the field's modulus {}", x, modulus),
+                ));
+            }
+            let x = ((x % &modulus) + &modulus) % &modulus;
+            Ok(Fr::from_str_vartime(&x.to_string()).unwrap())
+        })
+        .collect()
+}
+
+fn from_field_elements<Fr: PrimeField>(values: &[Fr]) -> Vec<BigInt> {
+    to_hex_strings(values)
+        .iter()
+        .map(|x| BigInt::from_str_radix(x, 16).unwrap())
+        .collect()
+}
+
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn pick_cycle_from_prime() {
+        use super::*;
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy");
+        let private_inputs = (0..2)
+            .map(|adder| HashMap::from([("adder".to_string(), serde_json::json!(adder))]))
+            .collect::<Vec<_>>();
+        let z0 = [BigInt::from(10), BigInt::from(10)];
+
+        let mut pps = vec![];
+        for (curve, cycle) in [
+            ("bn254", CurveCycle::Bn256Grumpkin),
+            ("pasta", CurveCycle::PallasVesta),
+        ] {
+            let r1cs =
+                DynR1CS::load(&FileLocation::PathBuf(root.join(curve).join("toy.r1cs"))).unwrap();
+            assert_eq!(r1cs.curve_cycle(), cycle);
+
+            // (10, 10) -> (10, 20) -> (11, 30)
+            let pp = r1cs.create_public_params();
+            let recursive_proof = create_recursive_circuit_dyn(
+                FileLocation::PathBuf(root.join(curve).join("toy_js/toy.wasm")),
+                r1cs.clone(),
+                private_inputs.clone(),
+                &z0,
+                &pp,
+            )
+            .unwrap();
+            let z_n = recursive_proof.verify(&pp).unwrap();
+            assert_eq!(z_n, vec![BigInt::from(11), BigInt::from(30)]);
+            pps.push((r1cs, pp));
+        }
+
+        let err = create_recursive_circuit_dyn(
+            FileLocation::PathBuf(root.join("pasta/toy_js/toy.wasm")),
+            pps[1].0.clone(),
+            private_inputs,
+            &z0,
+            &pps[0].1,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            "r1cs is over PallasVesta, but the public params are for Bn256Grumpkin"
+        );
+
+        let json =
+            r#"{"prime": "7", "constraints": [], "nPubInputs": 2, "nOutputs": 1, "nVars": 3}"#;
+        let err = DynR1CS::from_bytes(json.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // truncated or corrupt r1cs files are errors, not panics
+        let bytes = std::fs::read(root.join("pasta/toy.r1cs")).unwrap();
+        let err = DynR1CS::from_bytes(&bytes[..bytes.len() / 2]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let mut corrupt = bytes.clone();
+        corrupt[4] = 2; // version
+        let err = DynR1CS::from_bytes(&corrupt).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn secq256k1_cycle() {
+        use super::*;
+        use crate::create_recursive_circuit_from_witnesses;
+        use std::path::PathBuf;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/secq256k1");
+        let r1cs = DynR1CS::load(&FileLocation::PathBuf(root.join("toy.r1cs.json"))).unwrap();
+        assert_eq!(r1cs.curve_cycle(), CurveCycle::Secq256k1Secp256k1);
+        let pp = r1cs.create_public_params();
+        let DynR1CS::Secq256k1Secp256k1(r1cs) = r1cs else {
+            unreachable!()
+        };
+        let DynPublicParams::Secq256k1Secp256k1(secq_pp) = &pp else {
+            unreachable!()
+        };
+
+        // (10, 10) -> (10, 20) -> (11, 30), with witnesses [1, step_out, step_in]
+        let witnesses = [[1, 10, 20, 10, 10], [1, 11, 30, 10, 20]]
+            .iter()
+            .map(|witness| witness.iter().map(|x| F::<Secq256k1>::from(*x)).collect())
+            .collect();
+        let z0 = [BigInt::from(10), BigInt::from(10)];
+        let recursive_proof = create_recursive_circuit_from_witnesses(
+            r1cs,
+            witnesses,
+            to_field_elements(&z0).unwrap(),
+            secq_pp,
+        )
+        .unwrap();
+        let dyn_proof = DynRecursiveProof::Secq256k1Secp256k1(recursive_proof);
+        let z_n = dyn_proof.verify(&pp).unwrap();
+        assert_eq!(z_n, vec![BigInt::from(11), BigInt::from(30)]);
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    #[ignore = "needs examples/toy/secq256k1 compiled by examples/toy/compile.sh with a circom \
+                supporting --prime secq256k1"]
+    fn secq256k1_compiled_toy() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/secq256k1");
+        let r1cs = load_r1cs::<Secq256k1, Secp256k1>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let pp = create_public_params::<Secq256k1, Secp256k1>(r1cs.clone());
+
+        // (10, 10) -> (10, 20) -> (11, 30)
+        let private_inputs = (0..2)
+            .map(|adder| HashMap::from([("adder".to_string(), serde_json::json!(adder))]))
+            .collect::<Vec<_>>();
+        let z0 = vec![F::<Secq256k1>::from(10), F::<Secq256k1>::from(10)];
+        let recursive_proof = create_recursive_circuit(
+            FileLocation::PathBuf(root.join("toy_js/toy.wasm")),
+            r1cs,
+            private_inputs,
+            z0,
+            &pp,
+        )
+        .unwrap();
+        let z_n = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(
+            z_n,
+            vec![F::<Secq256k1>::from(11), F::<Secq256k1>::from(30)]
+        );
+    }
+}
@@ -0,0 +1,1168 @@
+//! A versioned JSON format for verifier keys and compressed proofs, for verifiers outside of
+//! Rust. Every field of the key and proof is spelled out: curve points are affine coordinates
+//! `{"x": ..., "y": ...}` and field elements are decimal strings. Field names follow nova-snark's,
+//! so each can be looked up in its verifier. The documents also carry the nova-snark version whose
+//! proof system they describe.
+//!
+//! Version 1 of the format is:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "curve_cycle": "bn256_grumpkin" | "pallas_vesta" | "secq256k1_secp256k1",
+//!   "nova_snark_version": "0.24",
+//!   "verifier_key": {
+//!     "arity_primary": 2,
+//!     "arity_secondary": 1,
+//!     "pp_digest": "123...",
+//!     "vk_primary": {
+//!       "ck_v": [{"x": "1", "y": "2"}, ...],
+//!       "ck_s": [{"x": "3", "y": "4"}],
+//!       "shape": {
+//!         "num_constraints": 10000,
+//!         "num_variables": 10000,
+//!         "num_io": 2,
+//!         "a": [{"row": 0, "column": 1, "value": "1"}, ...],
+//!         "b": [...],
+//!         "c": [...]
+//!       }
+//!     },
+//!     "vk_secondary": { ... }
+//!   }
+//! }
+//! ```
+//!
+//! for verifier keys, and for compressed proofs:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "curve_cycle": "bn256_grumpkin" | "pallas_vesta" | "secq256k1_secp256k1",
+//!   "nova_snark_version": "0.24",
+//!   "num_steps": 2,
+//!   "z0": ["10", "10"],
+//!   "z_n": ["11", "30"],
+//!   "z0_secondary": ["0"],
+//!   "proof": {
+//!     "r_u_primary": {"comm_w": {"x": ..., "y": ...}, "comm_e": {...}, "x": [...], "u": "..."},
+//!     "r_w_snark_primary": {
+//!       "sc_proof_outer": {"compressed_polys": [["1", "2", "3"], ...]},
+//!       "claims_outer": ["...", "...", "..."],
+//!       "eval_e": "...",
+//!       "sc_proof_inner": {...},
+//!       "eval_w": "...",
+//!       "sc_proof_batch": {...},
+//!       "evals_batch": [...],
+//!       "eval_arg": {"l_vec": [{...}, ...], "r_vec": [...], "a_hat": "..."}
+//!     },
+//!     "r_u_secondary": {...},
+//!     "l_u_secondary": {"comm_w": {...}, "x": [...]},
+//!     "nifs_secondary": {"comm_t": {...}},
+//!     "f_w_snark_secondary": {...},
+//!     "zn_primary": [...],
+//!     "zn_secondary": [...]
+//!   }
+//! }
+//! ```
+//!
+//! The points of the primary proof (`r_u_primary`, `r_w_snark_primary`, `vk_primary`) are on the
+//! primary curve and its field elements are in the primary scalar field, and likewise for the
+//! secondary ones. The identity is written as `(0, 0)`, which is on none of the supported curves.
+
+use std::io;
+use std::marker::PhantomData;
+
+use ff::{Field, PrimeField};
+use nova_snark::{
+    provider::{self, pedersen::CommitmentEngine},
+    traits::{circuit::StepCircuit, CompressedGroup, Group, ROConstants},
+    CompressedSNARK, VerifierKey,
+};
+use num_bigint::BigInt;
+use pasta_curves::arithmetic::CurveAffine;
+use pasta_curves::group::prime::PrimeCurveAffine;
+use serde::{Deserialize, Serialize};
+
+use crate::verifier::verification_error;
+use crate::{F, S};
+
+/// The version of the format written by `export_verifier_key` and `export_compressed_snark`.
+pub const JSON_VERSION: u32 = 1;
+
+/// The nova-snark release whose verifier key and proof the documents describe.
+pub const NOVA_SNARK_VERSION: &str = "0.24";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VerifierKeyJson {
+    pub version: u32,
+    /// `bn256_grumpkin`, `pallas_vesta` or `secq256k1_secp256k1`, named by the primary curve
+    /// first.
+    pub curve_cycle: String,
+    pub nova_snark_version: String,
+    pub verifier_key: NovaVerifierKeyJson,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompressedSNARKJson {
+    pub version: u32,
+    /// `bn256_grumpkin`, `pallas_vesta` or `secq256k1_secp256k1`, named by the primary curve
+    /// first.
+    pub curve_cycle: String,
+    pub nova_snark_version: String,
+    pub num_steps: usize,
+    /// The primary circuit's `step_in` of the first step, in decimal.
+    pub z0: Vec<String>,
+    /// The primary circuit's `step_out` of the last step, in decimal.
+    pub z_n: Vec<String>,
+    /// The secondary circuit's `step_in` of the first step, in decimal.
+    pub z0_secondary: Vec<String>,
+    pub proof: NovaProofJson,
+}
+
+/// A curve point in affine coordinates, in decimal. The identity is `(0, 0)`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PointJson {
+    pub x: String,
+    pub y: String,
+}
+
+/// Nova's `VerifierKey` for `CompressedSNARK`s.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NovaVerifierKeyJson {
+    /// Number of values in the primary circuit's `z_i`.
+    pub arity_primary: usize,
+    /// Number of values in the secondary circuit's `z_i`.
+    pub arity_secondary: usize,
+    /// Digest of the public params, hashed into every folded instance.
+    pub pp_digest: String,
+    /// Key of the Spartan proof of the primary circuit's folded instance.
+    pub vk_primary: SpartanVerifierKeyJson,
+    /// Key of the Spartan proof of the secondary circuit's folded instance.
+    pub vk_secondary: SpartanVerifierKeyJson,
+}
+
+/// Verifier key of Spartan with an IPA polynomial commitment.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpartanVerifierKeyJson {
+    /// Pedersen generators the witness and error vectors are committed with.
+    pub ck_v: Vec<PointJson>,
+    /// The generator the IPA commits to its inner products with.
+    pub ck_s: Vec<PointJson>,
+    /// The R1CS of Nova's augmented circuit, wrapping the step circuit.
+    pub shape: R1CSShapeJson,
+}
+
+/// Sparse R1CS matrices `A`, `B` and `C` over the variables `(W, 1, X)`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct R1CSShapeJson {
+    /// Number of rows of the matrices.
+    pub num_constraints: usize,
+    /// Number of witness variables `W`.
+    pub num_variables: usize,
+    /// Number of public inputs `X`.
+    pub num_io: usize,
+    /// Nonzero entries of `A`.
+    pub a: Vec<MatrixEntryJson>,
+    /// Nonzero entries of `B`.
+    pub b: Vec<MatrixEntryJson>,
+    /// Nonzero entries of `C`.
+    pub c: Vec<MatrixEntryJson>,
+}
+
+/// A nonzero entry of a sparse matrix.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MatrixEntryJson {
+    /// Index of the constraint.
+    pub row: usize,
+    /// Index of the variable, with witness variables first, then `1`, then the public inputs.
+    pub column: usize,
+    /// The coefficient, in decimal.
+    pub value: String,
+}
+
+/// Nova's `CompressedSNARK`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NovaProofJson {
+    /// The primary circuit's folded relaxed R1CS instance.
+    pub r_u_primary: RelaxedR1CSInstanceJson,
+    /// Spartan proof that `r_u_primary` is satisfied.
+    pub r_w_snark_primary: SpartanProofJson,
+    /// The secondary circuit's folded relaxed R1CS instance.
+    pub r_u_secondary: RelaxedR1CSInstanceJson,
+    /// The secondary circuit's instance of the last step, not yet folded.
+    pub l_u_secondary: R1CSInstanceJson,
+    /// Proof folding `l_u_secondary` into `r_u_secondary`.
+    pub nifs_secondary: NifsJson,
+    /// Spartan proof that the folded secondary instance is satisfied.
+    pub f_w_snark_secondary: SpartanProofJson,
+    /// The primary circuit's `z_n`, in decimal.
+    pub zn_primary: Vec<String>,
+    /// The secondary circuit's `z_n`, in decimal.
+    pub zn_secondary: Vec<String>,
+}
+
+/// A relaxed R1CS instance.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RelaxedR1CSInstanceJson {
+    /// Commitment to the witness.
+    pub comm_w: PointJson,
+    /// Commitment to the error vector.
+    pub comm_e: PointJson,
+    /// Public inputs, in decimal.
+    pub x: Vec<String>,
+    /// The relaxation scalar, in decimal.
+    pub u: String,
+}
+
+/// An R1CS instance.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct R1CSInstanceJson {
+    /// Commitment to the witness.
+    pub comm_w: PointJson,
+    /// Public inputs, in decimal.
+    pub x: Vec<String>,
+}
+
+/// Nova's non-interactive folding proof.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NifsJson {
+    /// Commitment to the cross term.
+    pub comm_t: PointJson,
+}
+
+/// A Spartan proof with an IPA polynomial commitment.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpartanProofJson {
+    /// Sum-check proof of the outer sum.
+    pub sc_proof_outer: SumcheckProofJson,
+    /// Claimed evaluations of `Az`, `Bz` and `Cz`, in decimal.
+    pub claims_outer: [String; 3],
+    /// Claimed evaluation of the error vector, in decimal.
+    pub eval_e: String,
+    /// Sum-check proof of the inner sum.
+    pub sc_proof_inner: SumcheckProofJson,
+    /// Claimed evaluation of the witness, in decimal.
+    pub eval_w: String,
+    /// Sum-check proof batching the evaluation claims of the witness and error vector.
+    pub sc_proof_batch: SumcheckProofJson,
+    /// Evaluations of the witness and error vector at the batched point, in decimal.
+    pub evals_batch: Vec<String>,
+    /// IPA proof of the batched evaluation.
+    pub eval_arg: IpaJson,
+}
+
+/// A sum-check proof.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SumcheckProofJson {
+    /// One polynomial per round, as its coefficients in decimal from the constant term up, with
+    /// the linear term left out since the verifier recovers it from the round's claim.
+    pub compressed_polys: Vec<Vec<String>>,
+}
+
+/// An inner product argument.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IpaJson {
+    /// The left commitment of each round.
+    pub l_vec: Vec<PointJson>,
+    /// The right commitment of each round.
+    pub r_vec: Vec<PointJson>,
+    /// The folded vector's single remaining value, in decimal.
+    pub a_hat: String,
+}
+
+/// A Nova group with Pedersen commitments whose points can be written as affine coordinates,
+/// which holds for every supported curve.
+pub trait ExportGroup: Group<CE = CommitmentEngine<Self>> {
+    fn to_json(&self) -> PointJson;
+
+    fn from_json(point: &PointJson) -> Result<Self, io::Error>;
+
+    fn preprocessed_to_json(point: &Self::PreprocessedGroupElement) -> PointJson;
+
+    fn preprocessed_from_json(
+        point: &PointJson,
+    ) -> Result<Self::PreprocessedGroupElement, io::Error>;
+}
+
+impl<G> ExportGroup for G
+where
+    G: Group<CE = CommitmentEngine<G>>,
+    G::PreprocessedGroupElement: CurveAffine<CurveExt = G, Base = G::Base>,
+{
+    fn to_json(&self) -> PointJson {
+        let (x, y, is_infinity) = self.to_coordinates();
+        if is_infinity {
+            return PointJson {
+                x: "0".to_string(),
+                y: "0".to_string(),
+            };
+        }
+        PointJson {
+            x: to_decimal(&x),
+            y: to_decimal(&y),
+        }
+    }
+
+    fn from_json(point: &PointJson) -> Result<Self, io::Error> {
+        Ok(Self::preprocessed_from_json(point)?.to_curve())
+    }
+
+    fn preprocessed_to_json(point: &Self::PreprocessedGroupElement) -> PointJson {
+        point.to_curve().to_json()
+    }
+
+    fn preprocessed_from_json(
+        point: &PointJson,
+    ) -> Result<Self::PreprocessedGroupElement, io::Error> {
+        let x = from_decimal::<G::Base>(&point.x)?;
+        let y = from_decimal::<G::Base>(&point.y)?;
+        if bool::from(x.is_zero() & y.is_zero()) {
+            return Ok(G::PreprocessedGroupElement::identity());
+        }
+        Option::from(G::PreprocessedGroupElement::from_xy(x, y)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("({}, {}) is not on the curve", point.x, point.y),
+            )
+        })
+    }
+}
+
+/// Export the verifier key of a `CompressedSNARK` of any primary and secondary step circuits.
+pub fn export_verifier_key<G1, G2, C1, C2>(
+    vk: &VerifierKey<G1, G2, C1, C2, S<G1>, S<G2>>,
+) -> Result<VerifierKeyJson, io::Error>
+where
+    G1: ExportGroup<Base = <G2 as Group>::Scalar>,
+    G2: ExportGroup<Base = <G1 as Group>::Scalar>,
+    C1: StepCircuit<F<G1>>,
+    C2: StepCircuit<F<G2>>,
+{
+    let raw: RawVerifierKey<G1, G2> = serde_json::from_value(serde_json::to_value(vk)?)?;
+    Ok(VerifierKeyJson {
+        version: JSON_VERSION,
+        curve_cycle: curve_cycle::<G1>()?.to_string(),
+        nova_snark_version: NOVA_SNARK_VERSION.to_string(),
+        verifier_key: NovaVerifierKeyJson {
+            arity_primary: raw.F_arity_primary,
+            arity_secondary: raw.F_arity_secondary,
+            pp_digest: to_decimal(&raw.pp_digest),
+            vk_primary: raw.vk_primary.to_json(),
+            vk_secondary: raw.vk_secondary.to_json(),
+        },
+    })
+}
+
+/// Export a compressed proof of `num_steps` steps taking `z0` to `z_n`, with the secondary
+/// circuit started from `z0_secondary` (`[0]` for the proving functions without
+/// `_with_secondary`).
+pub fn export_compressed_snark<G1, G2, C1, C2>(
+    compressed_snark: &CompressedSNARK<G1, G2, C1, C2, S<G1>, S<G2>>,
+    num_steps: usize,
+    z0: &[F<G1>],
+    z_n: &[F<G1>],
+    z0_secondary: &[F<G2>],
+) -> Result<CompressedSNARKJson, io::Error>
+where
+    G1: ExportGroup<Base = <G2 as Group>::Scalar>,
+    G2: ExportGroup<Base = <G1 as Group>::Scalar>,
+    C1: StepCircuit<F<G1>>,
+    C2: StepCircuit<F<G2>>,
+{
+    let raw: RawCompressedSNARK<G1, G2> =
+        serde_json::from_value(serde_json::to_value(compressed_snark)?)?;
+    Ok(CompressedSNARKJson {
+        version: JSON_VERSION,
+        curve_cycle: curve_cycle::<G1>()?.to_string(),
+        nova_snark_version: NOVA_SNARK_VERSION.to_string(),
+        num_steps,
+        z0: to_decimal_strings(z0),
+        z_n: to_decimal_strings(z_n),
+        z0_secondary: to_decimal_strings(z0_secondary),
+        proof: NovaProofJson {
+            r_u_primary: raw.r_U_primary.to_json(),
+            r_w_snark_primary: raw.r_W_snark_primary.to_json()?,
+            r_u_secondary: raw.r_U_secondary.to_json(),
+            l_u_secondary: raw.l_u_secondary.to_json(),
+            nifs_secondary: NifsJson {
+                comm_t: raw.nifs_secondary.comm_T.to_json()?,
+            },
+            f_w_snark_secondary: raw.f_W_snark_secondary.to_json()?,
+            zn_primary: to_decimal_strings(&raw.zn_primary),
+            zn_secondary: to_decimal_strings(&raw.zn_secondary),
+        },
+    })
+}
+
+impl VerifierKeyJson {
+    /// The verifier key, failing if the document's version or curve cycle don't match.
+    #[allow(clippy::type_complexity)]
+    pub fn verifier_key<G1, G2, C1, C2>(
+        &self,
+    ) -> Result<VerifierKey<G1, G2, C1, C2, S<G1>, S<G2>>, io::Error>
+    where
+        G1: ExportGroup<Base = <G2 as Group>::Scalar>,
+        G2: ExportGroup<Base = <G1 as Group>::Scalar>,
+        C1: StepCircuit<F<G1>>,
+        C2: StepCircuit<F<G2>>,
+    {
+        check_header::<G1>(self.version, &self.curve_cycle, &self.nova_snark_version)?;
+        let vk = &self.verifier_key;
+        let raw = RawVerifierKey::<G1, G2> {
+            F_arity_primary: vk.arity_primary,
+            F_arity_secondary: vk.arity_secondary,
+            ro_consts_primary: ROConstants::<G1>::default(),
+            ro_consts_secondary: ROConstants::<G2>::default(),
+            pp_digest: from_decimal(&vk.pp_digest)?,
+            vk_primary: RawSpartanVerifierKey::from_json(&vk.vk_primary)?,
+            vk_secondary: RawSpartanVerifierKey::from_json(&vk.vk_secondary)?,
+            _p_c1: PhantomData,
+            _p_c2: PhantomData,
+        };
+        Ok(serde_json::from_value(serde_json::to_value(raw)?)?)
+    }
+}
+
+impl CompressedSNARKJson {
+    /// The compressed proof, failing if the document's version or curve cycle don't match.
+    #[allow(clippy::type_complexity)]
+    pub fn compressed_snark<G1, G2, C1, C2>(
+        &self,
+    ) -> Result<CompressedSNARK<G1, G2, C1, C2, S<G1>, S<G2>>, io::Error>
+    where
+        G1: ExportGroup<Base = <G2 as Group>::Scalar>,
+        G2: ExportGroup<Base = <G1 as Group>::Scalar>,
+        C1: StepCircuit<F<G1>>,
+        C2: StepCircuit<F<G2>>,
+    {
+        check_header::<G1>(self.version, &self.curve_cycle, &self.nova_snark_version)?;
+        let proof = &self.proof;
+        let raw = RawCompressedSNARK::<G1, G2> {
+            r_U_primary: RawRelaxedR1CSInstance::from_json(&proof.r_u_primary)?,
+            r_W_snark_primary: RawSpartanProof::from_json(&proof.r_w_snark_primary)?,
+            r_U_secondary: RawRelaxedR1CSInstance::from_json(&proof.r_u_secondary)?,
+            l_u_secondary: RawR1CSInstance::from_json(&proof.l_u_secondary)?,
+            nifs_secondary: RawNifs {
+                comm_T: RawCompressedCommitment::from_json(&proof.nifs_secondary.comm_t)?,
+            },
+            f_W_snark_secondary: RawSpartanProof::from_json(&proof.f_w_snark_secondary)?,
+            zn_primary: from_decimal_strings(&proof.zn_primary)?,
+            zn_secondary: from_decimal_strings(&proof.zn_secondary)?,
+            _p_c1: PhantomData,
+            _p_c2: PhantomData,
+        };
+        Ok(serde_json::from_value(serde_json::to_value(raw)?)?)
+    }
+
+    /// Verify the proof of the step circuits `C1` and `C2` against `vk`, checking that it takes
+    /// `z0` and `z0_secondary` to `z_n`.
+    pub fn verify<G1, G2, C1, C2>(&self, vk: &VerifierKeyJson) -> Result<(), io::Error>
+    where
+        G1: ExportGroup<Base = <G2 as Group>::Scalar>,
+        G2: ExportGroup<Base = <G1 as Group>::Scalar>,
+        C1: StepCircuit<F<G1>>,
+        C2: StepCircuit<F<G2>>,
+    {
+        let vk = vk.verifier_key::<G1, G2, C1, C2>()?;
+        let compressed_snark = self.compressed_snark::<G1, G2, C1, C2>()?;
+        let z0 = from_decimal_strings::<F<G1>>(&self.z0)?;
+        let z_n = from_decimal_strings::<F<G1>>(&self.z_n)?;
+        let z0_secondary = from_decimal_strings::<F<G2>>(&self.z0_secondary)?;
+
+        let (zn_primary, _) = compressed_snark
+            .verify(&vk, self.num_steps, z0, z0_secondary)
+            .map_err(verification_error)?;
+        if zn_primary != z_n {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "proof does not output z_n",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// nova-snark keeps the fields of its keys and proofs private, so these mirror how it serializes
+// them, field for field, to get at the points and scalars. `deny_unknown_fields` makes a change
+// to nova-snark's layout fail loudly instead of dropping fields.
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawVerifierKey<G1: Group, G2: Group> {
+    F_arity_primary: usize,
+    F_arity_secondary: usize,
+    ro_consts_primary: ROConstants<G1>,
+    ro_consts_secondary: ROConstants<G2>,
+    pp_digest: G1::Scalar,
+    vk_primary: RawSpartanVerifierKey<G1>,
+    vk_secondary: RawSpartanVerifierKey<G2>,
+    _p_c1: PhantomData<()>,
+    _p_c2: PhantomData<()>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawSpartanVerifierKey<G: Group> {
+    vk_ee: RawIpaVerifierKey<G>,
+    S: RawR1CSShape<G>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawIpaVerifierKey<G: Group> {
+    ck_v: RawCommitmentKey<G>,
+    ck_s: RawCommitmentKey<G>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawCommitmentKey<G: Group> {
+    ck: Vec<G::PreprocessedGroupElement>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawR1CSShape<G: Group> {
+    num_cons: usize,
+    num_vars: usize,
+    num_io: usize,
+    A: Vec<(usize, usize, G::Scalar)>,
+    B: Vec<(usize, usize, G::Scalar)>,
+    C: Vec<(usize, usize, G::Scalar)>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawCompressedSNARK<G1: Group, G2: Group> {
+    r_U_primary: RawRelaxedR1CSInstance<G1>,
+    r_W_snark_primary: RawSpartanProof<G1>,
+    r_U_secondary: RawRelaxedR1CSInstance<G2>,
+    l_u_secondary: RawR1CSInstance<G2>,
+    nifs_secondary: RawNifs<G2>,
+    f_W_snark_secondary: RawSpartanProof<G2>,
+    zn_primary: Vec<G1::Scalar>,
+    zn_secondary: Vec<G2::Scalar>,
+    _p_c1: PhantomData<()>,
+    _p_c2: PhantomData<()>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawCommitment<G: Group> {
+    comm: G,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawCompressedCommitment<G: Group> {
+    comm: G::CompressedGroupElement,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawRelaxedR1CSInstance<G: Group> {
+    comm_W: RawCommitment<G>,
+    comm_E: RawCommitment<G>,
+    X: Vec<G::Scalar>,
+    u: G::Scalar,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawR1CSInstance<G: Group> {
+    comm_W: RawCommitment<G>,
+    X: Vec<G::Scalar>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawNifs<G: Group> {
+    comm_T: RawCompressedCommitment<G>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawSpartanProof<G: Group> {
+    sc_proof_outer: RawSumcheckProof<G>,
+    claims_outer: (G::Scalar, G::Scalar, G::Scalar),
+    eval_E: G::Scalar,
+    sc_proof_inner: RawSumcheckProof<G>,
+    eval_W: G::Scalar,
+    sc_proof_batch: RawSumcheckProof<G>,
+    evals_batch: Vec<G::Scalar>,
+    eval_arg: RawIpa<G>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawSumcheckProof<G: Group> {
+    compressed_polys: Vec<RawCompressedUniPoly<G>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawCompressedUniPoly<G: Group> {
+    coeffs_except_linear_term: Vec<G::Scalar>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct RawIpa<G: Group> {
+    L_vec: Vec<RawCompressedCommitment<G>>,
+    R_vec: Vec<RawCompressedCommitment<G>>,
+    a_hat: G::Scalar,
+}
+
+impl<G: ExportGroup> RawSpartanVerifierKey<G> {
+    fn to_json(&self) -> SpartanVerifierKeyJson {
+        let points = |ck: &RawCommitmentKey<G>| ck.ck.iter().map(G::preprocessed_to_json).collect();
+        let entries = |m: &[(usize, usize, G::Scalar)]| {
+            m.iter()
+                .map(|(row, column, value)| MatrixEntryJson {
+                    row: *row,
+                    column: *column,
+                    value: to_decimal(value),
+                })
+                .collect()
+        };
+        SpartanVerifierKeyJson {
+            ck_v: points(&self.vk_ee.ck_v),
+            ck_s: points(&self.vk_ee.ck_s),
+            shape: R1CSShapeJson {
+                num_constraints: self.S.num_cons,
+                num_variables: self.S.num_vars,
+                num_io: self.S.num_io,
+                a: entries(&self.S.A),
+                b: entries(&self.S.B),
+                c: entries(&self.S.C),
+            },
+        }
+    }
+
+    fn from_json(vk: &SpartanVerifierKeyJson) -> Result<Self, io::Error> {
+        let points = |ck: &[PointJson]| -> Result<_, io::Error> {
+            Ok(RawCommitmentKey {
+                ck: ck
+                    .iter()
+                    .map(G::preprocessed_from_json)
+                    .collect::<Result<_, _>>()?,
+            })
+        };
+        let entries = |m: &[MatrixEntryJson]| {
+            m.iter()
+                .map(|entry| Ok((entry.row, entry.column, from_decimal(&entry.value)?)))
+                .collect::<Result<_, io::Error>>()
+        };
+        Ok(RawSpartanVerifierKey {
+            vk_ee: RawIpaVerifierKey {
+                ck_v: points(&vk.ck_v)?,
+                ck_s: points(&vk.ck_s)?,
+            },
+            S: RawR1CSShape {
+                num_cons: vk.shape.num_constraints,
+                num_vars: vk.shape.num_variables,
+                num_io: vk.shape.num_io,
+                A: entries(&vk.shape.a)?,
+                B: entries(&vk.shape.b)?,
+                C: entries(&vk.shape.c)?,
+            },
+        })
+    }
+}
+
+impl<G: ExportGroup> RawCompressedCommitment<G> {
+    fn to_json(&self) -> Result<PointJson, io::Error> {
+        self.comm
+            .decompress()
+            .map(|point| point.to_json())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "commitment is not a compressed point",
+                )
+            })
+    }
+
+    fn from_json(point: &PointJson) -> Result<Self, io::Error> {
+        Ok(RawCompressedCommitment {
+            comm: G::from_json(point)?.compress(),
+        })
+    }
+}
+
+impl<G: ExportGroup> RawRelaxedR1CSInstance<G> {
+    fn to_json(&self) -> RelaxedR1CSInstanceJson {
+        RelaxedR1CSInstanceJson {
+            comm_w: self.comm_W.comm.to_json(),
+            comm_e: self.comm_E.comm.to_json(),
+            x: to_decimal_strings(&self.X),
+            u: to_decimal(&self.u),
+        }
+    }
+
+    fn from_json(instance: &RelaxedR1CSInstanceJson) -> Result<Self, io::Error> {
+        Ok(RawRelaxedR1CSInstance {
+            comm_W: RawCommitment {
+                comm: G::from_json(&instance.comm_w)?,
+            },
+            comm_E: RawCommitment {
+                comm: G::from_json(&instance.comm_e)?,
+            },
+            X: from_decimal_strings(&instance.x)?,
+            u: from_decimal(&instance.u)?,
+        })
+    }
+}
+
+impl<G: ExportGroup> RawR1CSInstance<G> {
+    fn to_json(&self) -> R1CSInstanceJson {
+        R1CSInstanceJson {
+            comm_w: self.comm_W.comm.to_json(),
+            x: to_decimal_strings(&self.X),
+        }
+    }
+
+    fn from_json(instance: &R1CSInstanceJson) -> Result<Self, io::Error> {
+        Ok(RawR1CSInstance {
+            comm_W: RawCommitment {
+                comm: G::from_json(&instance.comm_w)?,
+            },
+            X: from_decimal_strings(&instance.x)?,
+        })
+    }
+}
+
+impl<G: ExportGroup> RawSpartanProof<G> {
+    fn to_json(&self) -> Result<SpartanProofJson, io::Error> {
+        let sumcheck = |proof: &RawSumcheckProof<G>| SumcheckProofJson {
+            compressed_polys: proof
+                .compressed_polys
+                .iter()
+                .map(|poly| to_decimal_strings(&poly.coeffs_except_linear_term))
+                .collect(),
+        };
+        let points = |commitments: &[RawCompressedCommitment<G>]| {
+            commitments
+                .iter()
+                .map(RawCompressedCommitment::to_json)
+                .collect::<Result<_, _>>()
+        };
+        let (claim_a, claim_b, claim_c) = &self.claims_outer;
+        Ok(SpartanProofJson {
+            sc_proof_outer: sumcheck(&self.sc_proof_outer),
+            claims_outer: [
+                to_decimal(claim_a),
+                to_decimal(claim_b),
+                to_decimal(claim_c),
+            ],
+            eval_e: to_decimal(&self.eval_E),
+            sc_proof_inner: sumcheck(&self.sc_proof_inner),
+            eval_w: to_decimal(&self.eval_W),
+            sc_proof_batch: sumcheck(&self.sc_proof_batch),
+            evals_batch: to_decimal_strings(&self.evals_batch),
+            eval_arg: IpaJson {
+                l_vec: points(&self.eval_arg.L_vec)?,
+                r_vec: points(&self.eval_arg.R_vec)?,
+                a_hat: to_decimal(&self.eval_arg.a_hat),
+            },
+        })
+    }
+
+    fn from_json(proof: &SpartanProofJson) -> Result<Self, io::Error> {
+        let sumcheck = |proof: &SumcheckProofJson| -> Result<_, io::Error> {
+            Ok(RawSumcheckProof {
+                compressed_polys: proof
+                    .compressed_polys
+                    .iter()
+                    .map(|coeffs| {
+                        Ok(RawCompressedUniPoly {
+                            coeffs_except_linear_term: from_decimal_strings(coeffs)?,
+                        })
+                    })
+                    .collect::<Result<_, io::Error>>()?,
+            })
+        };
+        let points = |points: &[PointJson]| {
+            points
+                .iter()
+                .map(RawCompressedCommitment::from_json)
+                .collect::<Result<_, _>>()
+        };
+        let [claim_a, claim_b, claim_c] = &proof.claims_outer;
+        Ok(RawSpartanProof {
+            sc_proof_outer: sumcheck(&proof.sc_proof_outer)?,
+            claims_outer: (
+                from_decimal(claim_a)?,
+                from_decimal(claim_b)?,
+                from_decimal(claim_c)?,
+            ),
+            eval_E: from_decimal(&proof.eval_e)?,
+            sc_proof_inner: sumcheck(&proof.sc_proof_inner)?,
+            eval_W: from_decimal(&proof.eval_w)?,
+            sc_proof_batch: sumcheck(&proof.sc_proof_batch)?,
+            evals_batch: from_decimal_strings(&proof.evals_batch)?,
+            eval_arg: RawIpa {
+                L_vec: points(&proof.eval_arg.l_vec)?,
+                R_vec: points(&proof.eval_arg.r_vec)?,
+                a_hat: from_decimal(&proof.eval_arg.a_hat)?,
+            },
+        })
+    }
+}
+
+fn curve_cycle<G1: Group>() -> Result<&'static str, io::Error> {
+    let modulus = F::<G1>::MODULUS;
+    if modulus == F::<provider::bn256_grumpkin::bn256::Point>::MODULUS {
+        Ok("bn256_grumpkin")
+    } else if modulus == F::<pasta_curves::pallas::Point>::MODULUS {
+        Ok("pallas_vesta")
+    } else if modulus == F::<provider::secp_secq::secq256k1::Point>::MODULUS {
+        Ok("secq256k1_secp256k1")
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "no curve cycle has a primary scalar field of modulus {}",
+                modulus
+            ),
+        ))
+    }
+}
+
+fn check_header<G1: Group>(
+    version: u32,
+    curve_cycle_name: &str,
+    nova_snark_version: &str,
+) -> Result<(), io::Error> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    if version != JSON_VERSION {
+        return Err(invalid(format!(
+            "unsupported version {}, expected {}",
+            version, JSON_VERSION
+        )));
+    }
+    if nova_snark_version != NOVA_SNARK_VERSION {
+        return Err(invalid(format!(
+            "made with nova-snark {}, but this is nova-snark {}",
+            nova_snark_version, NOVA_SNARK_VERSION
+        )));
+    }
+    let expected = curve_cycle::<G1>()?;
+    if curve_cycle_name != expected {
+        return Err(invalid(format!(
+            "made for the {} cycle, but verifying on {}",
+            curve_cycle_name, expected
+        )));
+    }
+    Ok(())
+}
+
+fn to_decimal<Fr: PrimeField>(x: &Fr) -> String {
+    let hex = format!("{:?}", x);
+    BigInt::parse_bytes(hex.strip_prefix("0x").unwrap().as_bytes(), 16)
+        .unwrap()
+        .to_string()
+}
+
+/// Parse a field element written as `to_decimal` writes it, rejecting values that are not less
+/// than the modulus, which would otherwise be reduced and give one element several encodings.
+fn from_decimal<Fr: PrimeField>(x: &str) -> Result<Fr, io::Error> {
+    Fr::from_str_vartime(x)
+        .filter(|value| to_decimal(value) == x)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{:?} is not a field element in decimal, less than the modulus",
+                    x
+                ),
+            )
+        })
+}
+
+pub(crate) fn to_decimal_strings<Fr: PrimeField>(values: &[Fr]) -> Vec<String> {
+    values.iter().map(to_decimal).collect()
+}
+
+pub(crate) fn from_decimal_strings<Fr: PrimeField>(
+    values: &[String],
+) -> Result<Vec<Fr>, io::Error> {
+    values.iter().map(|x| from_decimal(x)).collect()
+}
+
+#[cfg(feature = "prover")]
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn versioned_json_round_trip() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::ivc::IvcProver;
+        use crate::witness::NodeWitnessGenerator;
+        use crate::{
+            compress_recursive_snark, create_public_params_with_secondary, FileLocation, C1,
+        };
+        use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        type G1 = provider::bn256_grumpkin::bn256::Point;
+        type G2 = provider::bn256_grumpkin::grumpkin::Point;
+        type Fr = F<G1>;
+        type Fq = F<G2>;
+
+        // adds one to its input
+        #[derive(Clone)]
+        struct Increment;
+
+        impl StepCircuit<Fq> for Increment {
+            fn arity(&self) -> usize {
+                1
+            }
+
+            fn synthesize<CS: ConstraintSystem<Fq>>(
+                &self,
+                cs: &mut CS,
+                z: &[AllocatedNum<Fq>],
+            ) -> Result<Vec<AllocatedNum<Fq>>, SynthesisError> {
+                let y = AllocatedNum::alloc(cs.namespace(|| "increment"), || {
+                    z[0].get_value()
+                        .map(|x| x + Fq::ONE)
+                        .ok_or(SynthesisError::AssignmentMissing)
+                })?;
+                cs.enforce(
+                    || "increment constraint",
+                    |lc| lc + z[0].get_variable() + CS::one(),
+                    |lc| lc + CS::one(),
+                    |lc| lc + y.get_variable(),
+                );
+                Ok(vec![y])
+            }
+        }
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/bn254");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let pp = create_public_params_with_secondary::<G1, G2, _>(r1cs.clone(), Increment);
+        let witness_generator = NodeWitnessGenerator {
+            witness_output: std::env::temp_dir().join("versioned_json_round_trip.wtns"),
+            ..NodeWitnessGenerator::new(root.join("toy_js/toy.wasm")).unwrap()
+        };
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+        let mut prover = IvcProver::with_secondary(
+            &pp,
+            r1cs,
+            &witness_generator,
+            z0.clone(),
+            Increment,
+            vec![Fq::from(5)],
+        );
+        for adder in 0..2 {
+            prover
+                .step(HashMap::from([(
+                    "adder".to_string(),
+                    serde_json::json!(adder),
+                )]))
+                .unwrap();
+        }
+        let recursive_proof = prover.finish().unwrap();
+        let z_n = recursive_proof.verify(&pp).unwrap();
+        let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
+        let compressed_snark =
+            compress_recursive_snark(&pp, &pk, recursive_proof.recursive_snark()).unwrap();
+
+        let vk_json = serde_json::to_string(&export_verifier_key(&vk).unwrap()).unwrap();
+        let proof_json = serde_json::to_string(
+            &export_compressed_snark(
+                &compressed_snark,
+                2,
+                &z0,
+                &z_n,
+                recursive_proof.z0_secondary(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let vk: VerifierKeyJson = serde_json::from_str(&vk_json).unwrap();
+        let mut proof: CompressedSNARKJson = serde_json::from_str(&proof_json).unwrap();
+        assert_eq!(vk.version, 1);
+        assert_eq!(proof.curve_cycle, "bn256_grumpkin");
+        // (10, 10) -> (10, 20) -> (11, 30), and 5 -> 6 -> 7 on the secondary curve
+        assert_eq!(proof.z_n, vec!["11".to_string(), "30".to_string()]);
+        assert_eq!(proof.z0_secondary, vec!["5".to_string()]);
+        assert_eq!(proof.proof.zn_secondary, vec!["7".to_string()]);
+        proof.verify::<G1, G2, C1<G1>, Increment>(&vk).unwrap();
+
+        proof.z0_secondary = vec!["0".to_string()];
+        let err = proof.verify::<G1, G2, C1<G1>, Increment>(&vk).unwrap_err();
+        assert!(err.to_string().starts_with("verification failed"));
+        proof.z0_secondary = vec!["5".to_string()];
+
+        // the modulus plus 11 would reduce to 11, but only canonical values are accepted
+        let modulus = BigInt::parse_bytes(&Fr::MODULUS.as_bytes()[2..], 16).unwrap();
+        for z_n_0 in [(modulus + 11u32).to_string(), "011".to_string()] {
+            proof.z_n[0] = z_n_0;
+            let err = proof.verify::<G1, G2, C1<G1>, Increment>(&vk).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().ends_with("less than the modulus"));
+        }
+
+        proof.z_n[0] = "12".to_string();
+        let err = proof.verify::<G1, G2, C1<G1>, Increment>(&vk).unwrap_err();
+        assert_eq!(err.to_string(), "proof does not output z_n");
+
+        proof.version = 2;
+        let err = proof.verify::<G1, G2, C1<G1>, Increment>(&vk).unwrap_err();
+        assert_eq!(err.to_string(), "unsupported version 2, expected 1");
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn exported_field_names() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::{
+            compress_recursive_snark, create_public_params, create_recursive_circuit, FileLocation,
+            S,
+        };
+        use serde_json::Value;
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = F<G1>;
+
+        fn keys(value: &Value) -> Vec<&str> {
+            value
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(String::as_str)
+                .collect()
+        }
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+        let private_inputs = vec![HashMap::from([("adder".to_string(), serde_json::json!(0))])];
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+        let recursive_proof = create_recursive_circuit(
+            FileLocation::PathBuf(root.join("toy_js/toy.wasm")),
+            r1cs,
+            private_inputs,
+            z0.clone(),
+            &pp,
+        )
+        .unwrap();
+        let z_n = recursive_proof.verify(&pp).unwrap();
+        let (pk, vk) = CompressedSNARK::<_, _, _, _, S<G1>, S<G2>>::setup(&pp).unwrap();
+        let compressed_snark =
+            compress_recursive_snark(&pp, &pk, recursive_proof.recursive_snark()).unwrap();
+
+        let vk = serde_json::to_value(export_verifier_key(&vk).unwrap()).unwrap();
+        let proof = serde_json::to_value(
+            export_compressed_snark(&compressed_snark, 1, &z0, &z_n, &[F::<G2>::ZERO]).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            keys(&vk),
+            [
+                "curve_cycle",
+                "nova_snark_version",
+                "verifier_key",
+                "version"
+            ]
+        );
+        let vk = &vk["verifier_key"];
+        assert_eq!(
+            keys(vk),
+            [
+                "arity_primary",
+                "arity_secondary",
+                "pp_digest",
+                "vk_primary",
+                "vk_secondary"
+            ]
+        );
+        assert_eq!(vk["arity_primary"], 2);
+        assert_eq!(keys(&vk["vk_primary"]), ["ck_s", "ck_v", "shape"]);
+        assert_eq!(keys(&vk["vk_primary"]["ck_s"][0]), ["x", "y"]);
+        assert_eq!(
+            keys(&vk["vk_secondary"]["shape"]),
+            ["a", "b", "c", "num_constraints", "num_io", "num_variables"]
+        );
+        assert_eq!(
+            keys(&vk["vk_secondary"]["shape"]["a"][0]),
+            ["column", "row", "value"]
+        );
+
+        assert_eq!(
+            keys(&proof),
+            [
+                "curve_cycle",
+                "nova_snark_version",
+                "num_steps",
+                "proof",
+                "version",
+                "z0",
+                "z0_secondary",
+                "z_n"
+            ]
+        );
+        let proof = &proof["proof"];
+        assert_eq!(
+            keys(proof),
+            [
+                "f_w_snark_secondary",
+                "l_u_secondary",
+                "nifs_secondary",
+                "r_u_primary",
+                "r_u_secondary",
+                "r_w_snark_primary",
+                "zn_primary",
+                "zn_secondary"
+            ]
+        );
+        assert_eq!(proof["zn_primary"], serde_json::json!(["10", "20"]));
+        assert_eq!(keys(&proof["r_u_primary"]), ["comm_e", "comm_w", "u", "x"]);
+        assert_eq!(keys(&proof["r_u_primary"]["comm_w"]), ["x", "y"]);
+        assert_eq!(keys(&proof["l_u_secondary"]), ["comm_w", "x"]);
+        assert_eq!(keys(&proof["nifs_secondary"]), ["comm_t"]);
+        assert_eq!(
+            keys(&proof["f_w_snark_secondary"]),
+            [
+                "claims_outer",
+                "eval_arg",
+                "eval_e",
+                "eval_w",
+                "evals_batch",
+                "sc_proof_batch",
+                "sc_proof_inner",
+                "sc_proof_outer"
+            ]
+        );
+        assert_eq!(
+            keys(&proof["r_w_snark_primary"]["sc_proof_outer"]),
+            ["compressed_polys"]
+        );
+        assert_eq!(
+            keys(&proof["r_w_snark_primary"]["eval_arg"]),
+            ["a_hat", "l_vec", "r_vec"]
+        );
+        assert_eq!(
+            keys(&proof["r_w_snark_primary"]["eval_arg"]["l_vec"][0]),
+            ["x", "y"]
+        );
+    }
+}
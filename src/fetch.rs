@@ -0,0 +1,94 @@
+use std::io;
+#[cfg(feature = "http")]
+use std::io::Read;
+use std::sync::RwLock;
+
+type Fetcher = dyn Fn(&str) -> Result<Vec<u8>, io::Error> + Send + Sync;
+
+static URL_FETCHER: RwLock<Option<Box<Fetcher>>> = RwLock::new(None);
+
+/// Download `FileLocation::URL`s with `fetcher`, e.g. your HTTP client of choice.
+pub fn set_url_fetcher(
+    fetcher: impl Fn(&str) -> Result<Vec<u8>, io::Error> + Send + Sync + 'static,
+) {
+    *URL_FETCHER.write().unwrap() = Some(Box::new(fetcher));
+}
+
+/// Download `url` with the fetcher set by `set_url_fetcher`. If none was set, `url` is
+/// downloaded with `ureq` when the `http` feature is enabled, and fails otherwise.
+pub fn fetch_url(url: &str) -> Result<Vec<u8>, io::Error> {
+    if let Some(fetcher) = URL_FETCHER.read().unwrap().as_ref() {
+        return fetcher(url);
+    }
+
+    default_fetch_url(url)
+}
+
+#[cfg(feature = "http")]
+fn default_fetch_url(url: &str) -> Result<Vec<u8>, io::Error> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|err| io::Error::other(format!("fetching {} failed: {}", url, err)))?;
+    let mut bytes = Vec::new();
+    response.into_reader().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "http"))]
+fn default_fetch_url(url: &str) -> Result<Vec<u8>, io::Error> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "can't fetch {}: enable the `http` feature or call `fetch::set_url_fetcher`",
+            url
+        ),
+    ))
+}
+
+mod tests {
+    #[cfg(not(feature = "http"))]
+    #[test]
+    fn fetch_without_fetcher() {
+        use super::*;
+
+        let err = default_fetch_url("https://example.com/toy.r1cs").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn fetch_over_http() {
+        use super::*;
+        use std::io::Write;
+        use std::net::TcpListener;
+        use std::thread;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            for body in [&b"r1cs bytes"[..], &b"not found"[..]] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).unwrap();
+                let status = if body == b"not found" {
+                    "404 Not Found"
+                } else {
+                    "200 OK"
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+
+        let url = format!("http://{}/toy.r1cs", addr);
+        assert_eq!(default_fetch_url(&url).unwrap(), b"r1cs bytes");
+        assert!(default_fetch_url(&url).is_err());
+        server.join().unwrap();
+    }
+}
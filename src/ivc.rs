@@ -0,0 +1,357 @@
+//! Proving one step at a time, for applications that get each step's private input only after
+//! the previous step was folded, e.g. from user interaction or a stream of blocks.
+
+use std::io;
+
+use ff::Field;
+use nova_snark::{
+    traits::{
+        circuit::{StepCircuit, TrivialCircuit},
+        Group,
+    },
+    PublicParams,
+};
+
+use crate::cancel::CancellationToken;
+use crate::circom::circuit::R1CS;
+use crate::proof::RecursiveProof;
+#[cfg(not(target_family = "wasm"))]
+use crate::prove::block_on;
+use crate::prove::{self, CircomSteps};
+use crate::witness::{StepInput, WitnessGenerator};
+use crate::{C1, C2, F};
+
+/// An IVC prover for a Circom step circuit. Each call to `step` computes the witness of one step
+/// from the current `state()` and folds it, and `finish` returns the proof of every folded step.
+///
+/// The first step creates the `RecursiveSNARK` from the same witness it folds, so every witness is
+/// computed exactly once. A step whose witness can't be computed or doesn't satisfy the R1CS fails
+/// without changing the state, and can be retried with another private input.
+///
+/// Every step folds the same `circuit_secondary` on the secondary curve, `TrivialCircuit`
+/// unless the prover was made with `with_secondary` or `resume_with_secondary`.
+pub struct IvcProver<'a, G1, G2, W, SC = C2<G2>>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    SC: StepCircuit<F<G2>>,
+{
+    pp: &'a PublicParams<G1, G2, C1<G1>, SC>,
+    r1cs: R1CS<F<G1>>,
+    witness_generator: W,
+    circuit_secondary: SC,
+    z0: Vec<F<G1>>,
+    z0_secondary: Vec<F<G2>>,
+    recursive_proof: Option<RecursiveProof<G1, G2, C1<G1>, SC>>,
+}
+
+impl<'a, G1, G2, W> IvcProver<'a, G1, G2, W>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    W: WitnessGenerator<F<G1>>,
+{
+    /// A prover folding steps of `r1cs` starting from `z0`, with witnesses from
+    /// `witness_generator`.
+    pub fn new(
+        pp: &'a PublicParams<G1, G2, C1<G1>, C2<G2>>,
+        r1cs: R1CS<F<G1>>,
+        witness_generator: W,
+        z0: Vec<F<G1>>,
+    ) -> Self {
+        Self::with_secondary(
+            pp,
+            r1cs,
+            witness_generator,
+            z0,
+            TrivialCircuit::default(),
+            vec![G2::Scalar::ZERO],
+        )
+    }
+
+    /// A prover folding more steps into `recursive_proof`, continuing from its `z_n`.
+    pub fn resume(
+        pp: &'a PublicParams<G1, G2, C1<G1>, C2<G2>>,
+        r1cs: R1CS<F<G1>>,
+        witness_generator: W,
+        recursive_proof: RecursiveProof<G1, G2, C1<G1>, C2<G2>>,
+    ) -> Self {
+        Self::resume_with_secondary(
+            pp,
+            r1cs,
+            witness_generator,
+            recursive_proof,
+            TrivialCircuit::default(),
+        )
+    }
+}
+
+impl<'a, G1, G2, W, SC> IvcProver<'a, G1, G2, W, SC>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    W: WitnessGenerator<F<G1>>,
+    SC: StepCircuit<F<G2>>,
+{
+    /// Like `new`, folding `circuit_secondary` on the secondary curve at every step, starting
+    /// from `z0_secondary`.
+    pub fn with_secondary(
+        pp: &'a PublicParams<G1, G2, C1<G1>, SC>,
+        r1cs: R1CS<F<G1>>,
+        witness_generator: W,
+        z0: Vec<F<G1>>,
+        circuit_secondary: SC,
+        z0_secondary: Vec<F<G2>>,
+    ) -> Self {
+        Self {
+            pp,
+            r1cs,
+            witness_generator,
+            circuit_secondary,
+            z0,
+            z0_secondary,
+            recursive_proof: None,
+        }
+    }
+
+    /// Like `resume`, folding `circuit_secondary` on the secondary curve at every step.
+    pub fn resume_with_secondary(
+        pp: &'a PublicParams<G1, G2, C1<G1>, SC>,
+        r1cs: R1CS<F<G1>>,
+        witness_generator: W,
+        recursive_proof: RecursiveProof<G1, G2, C1<G1>, SC>,
+        circuit_secondary: SC,
+    ) -> Self {
+        Self {
+            pp,
+            r1cs,
+            witness_generator,
+            circuit_secondary,
+            z0: recursive_proof.z0_primary.clone(),
+            z0_secondary: recursive_proof.z0_secondary.clone(),
+            recursive_proof: Some(recursive_proof),
+        }
+    }
+
+    /// Fold one step with `private_input`, returning the new state.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn step<I: StepInput>(&mut self, private_input: I) -> Result<&[F<G1>], io::Error> {
+        block_on(self.fold(private_input))?;
+        Ok(self.state())
+    }
+
+    /// Fold one step with `private_input`, returning the new state.
+    #[cfg(target_family = "wasm")]
+    pub async fn step<I: StepInput>(&mut self, private_input: I) -> Result<&[F<G1>], io::Error> {
+        self.fold(private_input).await?;
+        Ok(self.state())
+    }
+
+    pub(crate) async fn fold<I: StepInput>(&mut self, private_input: I) -> Result<(), io::Error> {
+        let private_inputs = vec![private_input.to_signals()?];
+        let steps = CircomSteps {
+            witness_generator: &self.witness_generator,
+            r1cs: &self.r1cs,
+        };
+        let circuits_secondary = vec![self.circuit_secondary.clone()];
+        let cancel = CancellationToken::new();
+
+        match &mut self.recursive_proof {
+            Some(recursive_proof) => {
+                prove::resume(
+                    recursive_proof,
+                    &steps,
+                    private_inputs,
+                    circuits_secondary,
+                    self.pp,
+                    &mut (),
+                    &cancel,
+                )
+                .await?;
+            }
+            None => {
+                let recursive_proof = prove::create(
+                    &steps,
+                    private_inputs,
+                    self.z0.clone(),
+                    circuits_secondary,
+                    self.z0_secondary.clone(),
+                    self.pp,
+                    &mut (),
+                    &cancel,
+                )
+                .await?
+                .into_recursive_proof();
+                self.recursive_proof = Some(recursive_proof);
+            }
+        }
+        Ok(())
+    }
+
+    /// The current `z_i`: `z0` until a step is folded, then the `step_out` of the last step.
+    pub fn state(&self) -> &[F<G1>] {
+        match &self.recursive_proof {
+            Some(recursive_proof) => recursive_proof.z_n(),
+            None => &self.z0,
+        }
+    }
+
+    /// Number of folded steps, including those of a resumed proof.
+    pub fn num_steps(&self) -> usize {
+        self.recursive_proof
+            .as_ref()
+            .map_or(0, RecursiveProof::num_steps)
+    }
+
+    /// The proof of every folded step. Fails if no step was folded, since Nova can't prove zero
+    /// steps.
+    pub fn finish(self) -> Result<RecursiveProof<G1, G2, C1<G1>, SC>, io::Error> {
+        self.recursive_proof.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no step was folded, so there is nothing to prove",
+            )
+        })
+    }
+}
+
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn prove_interactively() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::witness::NodeWitnessGenerator;
+        use crate::{create_public_params, FileLocation};
+        use serde_json::json;
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = F<G1>;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+        let witness_generator = NodeWitnessGenerator {
+            witness_output: std::env::temp_dir().join("prove_interactively.wtns"),
+            ..NodeWitnessGenerator::new(root.join("toy_js/toy.wasm")).unwrap()
+        };
+        let adder = |adder: u64| HashMap::from([("adder".to_string(), json!(adder))]);
+
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+        let prover = IvcProver::new(&pp, r1cs.clone(), &witness_generator, z0.clone());
+        assert_eq!(prover.state(), z0);
+        assert!(prover.finish().is_err());
+
+        // (10, 10) -> (10, 20) -> (11, 30)
+        let mut prover = IvcProver::new(&pp, r1cs.clone(), &witness_generator, z0);
+        assert_eq!(prover.step(adder(0)).unwrap(), [Fr::from(10), Fr::from(20)]);
+        assert_eq!(prover.step(adder(1)).unwrap(), [Fr::from(11), Fr::from(30)]);
+
+        // a step with a bad input leaves the state as it was
+        assert!(prover.step(HashMap::<String, u64>::new()).is_err());
+        assert_eq!(prover.num_steps(), 2);
+        assert_eq!(prover.state(), [Fr::from(11), Fr::from(30)]);
+
+        let recursive_proof = prover.finish().unwrap();
+        assert_eq!(
+            recursive_proof.verify(&pp).unwrap(),
+            [Fr::from(11), Fr::from(30)]
+        );
+
+        // -> (13, 41)
+        let mut prover = IvcProver::resume(&pp, r1cs, &witness_generator, recursive_proof);
+        prover.step(adder(2)).unwrap();
+        let recursive_proof = prover.finish().unwrap();
+        assert_eq!(recursive_proof.num_steps(), 3);
+        assert_eq!(
+            recursive_proof.verify(&pp).unwrap(),
+            [Fr::from(13), Fr::from(41)]
+        );
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn prove_with_secondary() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::witness::NodeWitnessGenerator;
+        use crate::{create_public_params_with_secondary, FileLocation};
+        use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+        use serde_json::json;
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = F<G1>;
+        type Fq = F<G2>;
+
+        // adds one to its input
+        #[derive(Clone)]
+        struct Increment;
+
+        impl StepCircuit<Fq> for Increment {
+            fn arity(&self) -> usize {
+                1
+            }
+
+            fn synthesize<CS: ConstraintSystem<Fq>>(
+                &self,
+                cs: &mut CS,
+                z: &[AllocatedNum<Fq>],
+            ) -> Result<Vec<AllocatedNum<Fq>>, SynthesisError> {
+                let y = AllocatedNum::alloc(cs.namespace(|| "increment"), || {
+                    z[0].get_value()
+                        .map(|x| x + Fq::ONE)
+                        .ok_or(SynthesisError::AssignmentMissing)
+                })?;
+                cs.enforce(
+                    || "increment constraint",
+                    |lc| lc + z[0].get_variable() + CS::one(),
+                    |lc| lc + CS::one(),
+                    |lc| lc + y.get_variable(),
+                );
+                Ok(vec![y])
+            }
+        }
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let pp = create_public_params_with_secondary::<G1, G2, _>(r1cs.clone(), Increment);
+        let witness_generator = NodeWitnessGenerator {
+            witness_output: std::env::temp_dir().join("prove_with_secondary.wtns"),
+            ..NodeWitnessGenerator::new(root.join("toy_js/toy.wasm")).unwrap()
+        };
+        let adder = |adder: u64| HashMap::from([("adder".to_string(), json!(adder))]);
+
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+        let mut prover = IvcProver::with_secondary(
+            &pp,
+            r1cs.clone(),
+            &witness_generator,
+            z0,
+            Increment,
+            vec![Fq::ZERO],
+        );
+        prover.step(adder(0)).unwrap();
+        let recursive_proof = prover.finish().unwrap();
+
+        let mut prover = IvcProver::resume_with_secondary(
+            &pp,
+            r1cs,
+            &witness_generator,
+            recursive_proof,
+            Increment,
+        );
+        prover.step(adder(1)).unwrap();
+        let recursive_proof = prover.finish().unwrap();
+        assert_eq!(recursive_proof.num_steps(), 2);
+        assert_eq!(
+            recursive_proof.verify(&pp).unwrap(),
+            [Fr::from(11), Fr::from(30)]
+        );
+    }
+}
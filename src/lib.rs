@@ -1,430 +1,503 @@
-use std::{
-    collections::HashMap,
-    env::current_dir,
-    fs,
-    path::{Path, PathBuf},
-};
+#[cfg(feature = "prover")]
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
-use crate::circom::reader::generate_witness_from_bin;
+#[cfg(feature = "prover")]
+use cancel::{CancellationToken, Cancelled, ProvingOutcome};
 use circom::circuit::{CircomCircuit, R1CS};
-use ff::Field;
+#[cfg(feature = "prover")]
+use compose::ComposedCircuit;
+#[cfg(feature = "prover")]
+use ff::{Field, PrimeField};
+#[cfg(feature = "prover")]
+use ivc::IvcProver;
+#[cfg(feature = "prover")]
+use nova_snark::{
+    errors::NovaError, traits::snark::RelaxedR1CSSNARKTrait, CompressedSNARK, ProverKey,
+    RecursiveSNARK,
+};
 use nova_snark::{
-    traits::{circuit::TrivialTestCircuit, Group},
-    PublicParams, RecursiveSNARK,
+    traits::{
+        circuit::{StepCircuit, TrivialCircuit},
+        Group,
+    },
+    PublicParams,
 };
-use num_bigint::BigInt;
-use num_traits::Num;
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "prover")]
+use progress::ProgressObserver;
+#[cfg(feature = "prover")]
+use proof::RecursiveProof;
+#[cfg(all(feature = "prover", not(target_family = "wasm")))]
+use prove::block_on;
+#[cfg(feature = "prover")]
+use prove::{CircomSteps, ComposedSteps};
+#[cfg(feature = "prover")]
 use serde_json::Value;
+#[cfg(feature = "prover")]
+use witness::{
+    default_witness_generator, to_signals, PrecomputedWitnesses, StepInput, WitnessFiles,
+    WitnessGenerator,
+};
 
-#[cfg(not(target_family = "wasm"))]
-use crate::circom::reader::generate_witness_from_wasm;
-
-#[cfg(target_family = "wasm")]
-use crate::circom::wasm::generate_witness_from_wasm;
+/// Define a proving function once, as the body of an `async fn`. On wasm it is that `async fn`,
+/// since the browser's witness generators are asynchronous. Natively it is a blocking `fn` that
+/// runs the body with `block_on`, where it never has to wait. Either way it needs the `prover`
+/// feature.
+macro_rules! prover_fn {
+    (
+        $(#[$meta:meta])*
+        pub fn $name:ident<$($generic:ident),*>($($arg:ident: $arg_ty:ty),* $(,)?) -> $ret:ty
+        where $($rest:tt)*
+    ) => {
+        prover_fn!(@where [$(#[$meta])* $name [$($generic),*] [$($arg: $arg_ty),*] $ret] [] $($rest)*);
+    };
+    (
+        @where
+        [$(#[$meta:meta])* $name:ident [$($generic:ident),*] [$($arg:ident: $arg_ty:ty),*] $ret:ty]
+        [$($bound:tt)*]
+        { $($body:tt)* }
+    ) => {
+        $(#[$meta])*
+        #[cfg(all(feature = "prover", not(target_family = "wasm")))]
+        pub fn $name<$($generic),*>($($arg: $arg_ty),*) -> $ret
+        where
+            $($bound)*
+        {
+            block_on(async move { $($body)* })
+        }
+
+        $(#[$meta])*
+        #[cfg(all(feature = "prover", target_family = "wasm"))]
+        pub async fn $name<$($generic),*>($($arg: $arg_ty),*) -> $ret
+        where
+            $($bound)*
+        {
+            $($body)*
+        }
+    };
+    // collect the where clause up to the body
+    (@where $head:tt [$($bound:tt)*] $next:tt $($rest:tt)*) => {
+        prover_fn!(@where $head [$($bound)* $next] $($rest)*);
+    };
+}
 
+pub mod calldata;
+#[cfg(feature = "prover")]
+pub mod cancel;
 pub mod circom;
+#[cfg(feature = "prover")]
+pub mod compose;
+#[cfg(feature = "prover")]
+pub mod dynamic;
+pub mod export;
+#[cfg(all(feature = "prover", not(target_family = "wasm")))]
+pub mod fetch;
+#[cfg(feature = "prover")]
+pub mod ivc;
+#[cfg(feature = "prover")]
+pub mod progress;
+pub mod proof;
+#[cfg(feature = "prover")]
+mod prove;
+#[cfg(feature = "prover")]
+pub mod transcript;
+pub mod verifier;
+#[cfg(feature = "prover")]
+pub mod witness;
 
 pub type F<G> = <G as Group>::Scalar;
 pub type EE<G> = nova_snark::provider::ipa_pc::EvaluationEngine<G>;
 pub type S<G> = nova_snark::spartan::snark::RelaxedR1CSSNARK<G, EE<G>>;
 pub type C1<G> = CircomCircuit<<G as Group>::Scalar>;
-pub type C2<G> = TrivialTestCircuit<<G as Group>::Scalar>;
+pub type C2<G> = TrivialCircuit<<G as Group>::Scalar>;
 
+#[cfg(feature = "prover")]
 #[derive(Clone)]
 pub enum FileLocation {
     PathBuf(PathBuf),
+    /// Fetched with `fetch` in the browser, and with `fetch::fetch_url` natively.
     URL(String),
+    /// The contents of the file, e.g. from `include_bytes!`. An R1CS is read as JSON unless it
+    /// starts with the binary format's `r1cs` magic, and a witness generator is run as wasm.
+    Bytes(Arc<[u8]>),
 }
 
-pub fn create_public_params<G1, G2>(r1cs: R1CS<F<G1>>) -> PublicParams<G1, G2, C1<G1>, C2<G2>>
-where
-    G1: Group<Base = <G2 as Group>::Scalar>,
-    G2: Group<Base = <G1 as Group>::Scalar>,
-{
-    let circuit_primary = CircomCircuit {
-        r1cs,
-        witness: None,
-    };
-    let circuit_secondary = TrivialTestCircuit::default();
-
-    PublicParams::setup(circuit_primary.clone(), circuit_secondary.clone())
-}
-
-#[derive(Serialize, Deserialize)]
-struct CircomInput {
-    step_in: Vec<String>,
-
-    #[serde(flatten)]
-    extra: HashMap<String, Value>,
+#[cfg(feature = "prover")]
+impl From<PathBuf> for FileLocation {
+    fn from(path: PathBuf) -> Self {
+        FileLocation::PathBuf(path)
+    }
 }
 
-#[cfg(not(target_family = "wasm"))]
-fn compute_witness<G1, G2>(
-    current_public_input: Vec<String>,
-    private_input: HashMap<String, Value>,
-    witness_generator_file: FileLocation,
-    witness_generator_output: &Path,
-) -> Vec<<G1 as Group>::Scalar>
-where
-    G1: Group<Base = <G2 as Group>::Scalar>,
-    G2: Group<Base = <G1 as Group>::Scalar>,
-{
-    let decimal_stringified_input: Vec<String> = current_public_input
-        .iter()
-        .map(|x| BigInt::from_str_radix(x, 16).unwrap().to_str_radix(10))
-        .collect();
-
-    let input = CircomInput {
-        step_in: decimal_stringified_input.clone(),
-        extra: private_input.clone(),
-    };
-
-    let is_wasm = match &witness_generator_file {
-        FileLocation::PathBuf(path) => path.extension().unwrap_or_default() == "wasm",
-        FileLocation::URL(_) => true,
-    };
-    let input_json = serde_json::to_string(&input).unwrap();
-
-    if is_wasm {
-        generate_witness_from_wasm::<F<G1>>(
-            &witness_generator_file,
-            &input_json,
-            &witness_generator_output,
-        )
-    } else {
-        let witness_generator_file = match &witness_generator_file {
-            FileLocation::PathBuf(path) => path,
-            FileLocation::URL(_) => panic!("unreachable"),
-        };
-        generate_witness_from_bin::<F<G1>>(
-            &witness_generator_file,
-            &input_json,
-            &witness_generator_output,
-        )
+#[cfg(feature = "prover")]
+impl FileLocation {
+    /// The contents of the file: read from disk, fetched, or the bytes themselves.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read(&self) -> Result<Vec<u8>, std::io::Error> {
+        match self {
+            FileLocation::PathBuf(path) => std::fs::read(path),
+            FileLocation::URL(url) => fetch::fetch_url(url),
+            FileLocation::Bytes(bytes) => Ok(bytes.to_vec()),
+        }
     }
 }
 
-#[cfg(target_family = "wasm")]
-async fn compute_witness<G1, G2>(
-    current_public_input: Vec<String>,
-    private_input: HashMap<String, Value>,
-    witness_generator_file: FileLocation,
-) -> Vec<<G1 as Group>::Scalar>
+pub fn create_public_params<G1, G2>(r1cs: R1CS<F<G1>>) -> PublicParams<G1, G2, C1<G1>, C2<G2>>
 where
     G1: Group<Base = <G2 as Group>::Scalar>,
     G2: Group<Base = <G1 as Group>::Scalar>,
 {
-    let decimal_stringified_input: Vec<String> = current_public_input
-        .iter()
-        .map(|x| BigInt::from_str_radix(x, 16).unwrap().to_str_radix(10))
-        .collect();
-
-    let input = CircomInput {
-        step_in: decimal_stringified_input.clone(),
-        extra: private_input.clone(),
-    };
-
-    let is_wasm = match &witness_generator_file {
-        FileLocation::PathBuf(path) => path.extension().unwrap_or_default() == "wasm",
-        FileLocation::URL(_) => true,
-    };
-    let input_json = serde_json::to_string(&input).unwrap();
-
-    if is_wasm {
-        generate_witness_from_wasm::<F<G1>>(
-            &witness_generator_file,
-            &input_json,
-        )
-        .await
-    } else {
-        let root = current_dir().unwrap(); // compute path only when generating witness from a binary
-        let witness_generator_output = root.join("circom_witness.wtns");
-        let witness_generator_file = match &witness_generator_file {
-            FileLocation::PathBuf(path) => path,
-            FileLocation::URL(_) => panic!("unreachable"),
-        };
-        generate_witness_from_bin::<F<G1>>(
-            &witness_generator_file,
-            &input_json,
-            &witness_generator_output,
-        )
-    }
+    create_public_params_with_secondary(r1cs, TrivialCircuit::default())
 }
 
-#[cfg(not(target_family = "wasm"))]
-pub fn create_recursive_circuit<G1, G2>(
-    witness_generator_file: FileLocation,
+/// Create public params with a custom secondary step circuit in place of `TrivialCircuit`.
+/// `circuit_secondary` only needs to describe the shape of the secondary circuit, e.g. a
+/// `CircomCircuit` without a witness.
+pub fn create_public_params_with_secondary<G1, G2, SC>(
     r1cs: R1CS<F<G1>>,
-    private_inputs: Vec<HashMap<String, Value>>,
-    start_public_input: Vec<F<G1>>,
-    pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
-) -> Result<RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>, std::io::Error>
+    circuit_secondary: SC,
+) -> PublicParams<G1, G2, C1<G1>, SC>
 where
     G1: Group<Base = <G2 as Group>::Scalar>,
     G2: Group<Base = <G1 as Group>::Scalar>,
+    SC: StepCircuit<F<G2>>,
 {
-    let root = current_dir().unwrap();
-    let witness_generator_output = root.join("circom_witness.wtns");
+    let circuit_primary = CircomCircuit {
+        r1cs,
+        witness: None,
+    };
 
-    let iteration_count = private_inputs.len();
+    PublicParams::setup(&circuit_primary, &circuit_secondary)
+}
 
-    let start_public_input_hex = start_public_input
+#[cfg(feature = "prover")]
+fn to_hex_strings<Fr: PrimeField>(values: &[Fr]) -> Vec<String> {
+    values
         .iter()
         .map(|&x| format!("{:?}", x).strip_prefix("0x").unwrap().to_string())
-        .collect::<Vec<String>>();
-    let mut current_public_input = start_public_input_hex.clone();
-
-    let witness_0 = compute_witness::<G1, G2>(
-        current_public_input.clone(),
-        private_inputs[0].clone(),
-        witness_generator_file.clone(),
-        &witness_generator_output,
-    );
-
-    let circuit_0 = CircomCircuit {
-        r1cs: r1cs.clone(),
-        witness: Some(witness_0),
-    };
-    let circuit_secondary = TrivialTestCircuit::default();
-    let z0_secondary = vec![G2::Scalar::ZERO];
-
-    let mut recursive_snark = RecursiveSNARK::<G1, G2, C1<G1>, C2<G2>>::new(
-        &pp,
-        &circuit_0,
-        &circuit_secondary,
-        start_public_input.clone(),
-        z0_secondary.clone(),
-    );
+        .collect()
+}
 
-    for i in 0..iteration_count {
-        let witness = compute_witness::<G1, G2>(
-            current_public_input.clone(),
-            private_inputs[i].clone(),
-            witness_generator_file.clone(),
-            &witness_generator_output,
-        );
+prover_fn! {
+    /// Fold one step per private input, starting from `start_public_input`. Use
+    /// `ivc::IvcProver` to fold steps one at a time instead.
+    pub fn create_recursive_circuit<G1, G2, I>(
+        witness_generator_file: FileLocation,
+        r1cs: R1CS<F<G1>>,
+        private_inputs: Vec<I>,
+        start_public_input: Vec<F<G1>>,
+        pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
+    ) -> Result<RecursiveProof<G1, G2, C1<G1>, C2<G2>>, std::io::Error>
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+        I: StepInput,
+    {
+        let private_inputs = to_signals(&private_inputs)?;
+        let witness_generator = default_witness_generator(&witness_generator_file)?;
+        let mut prover = IvcProver::new(pp, r1cs, witness_generator, start_public_input);
+        for private_input in private_inputs {
+            prover.fold(private_input).await?;
+        }
+        prover.finish()
+    }
+}
 
-        let circuit = CircomCircuit {
-            r1cs: r1cs.clone(),
-            witness: Some(witness),
+prover_fn! {
+    /// Like `create_recursive_circuit`, but folds `circuits_secondary[i]` on the secondary curve
+    /// at step `i` instead of `TrivialCircuit`. `circuits_secondary` must have one circuit per
+    /// private input, and `z0_secondary` is the secondary circuit's initial input.
+    ///
+    /// `observer` is called after every folded step. If `cancel` is cancelled, proving stops
+    /// before the next step and the `RecursiveProof` folded so far is returned along with its
+    /// `z_i`.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn create_recursive_circuit_with_secondary<G1, G2, W, SC, O, I>(
+        witness_generator: &W,
+        r1cs: R1CS<F<G1>>,
+        private_inputs: Vec<I>,
+        start_public_input: Vec<F<G1>>,
+        circuits_secondary: Vec<SC>,
+        z0_secondary: Vec<F<G2>>,
+        pp: &PublicParams<G1, G2, C1<G1>, SC>,
+        observer: &mut O,
+        cancel: &CancellationToken,
+    ) -> Result<ProvingOutcome<RecursiveProof<G1, G2, C1<G1>, SC>, F<G1>>, std::io::Error>
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+        W: WitnessGenerator<F<G1>> + ?Sized,
+        SC: StepCircuit<F<G2>>,
+        O: ProgressObserver<F<G1>>,
+        I: StepInput,
+    {
+        let private_inputs = to_signals(&private_inputs)?;
+        let steps = CircomSteps {
+            witness_generator,
+            r1cs: &r1cs,
         };
-
-        let current_public_output = circuit.get_public_outputs();
-        current_public_input = current_public_output
-            .iter()
-            .map(|&x| format!("{:?}", x).strip_prefix("0x").unwrap().to_string())
-            .collect();
-
-        let res = recursive_snark.prove_step(
-            &pp,
-            &circuit,
-            &circuit_secondary,
-            start_public_input.clone(),
-            z0_secondary.clone(),
-        );
-        assert!(res.is_ok());
+        prove::create(
+            &steps,
+            private_inputs,
+            start_public_input,
+            circuits_secondary,
+            z0_secondary,
+            pp,
+            observer,
+            cancel,
+        )
+        .await
     }
-    fs::remove_file(witness_generator_output)?;
-
-    Ok(recursive_snark)
 }
 
-#[cfg(target_family = "wasm")]
-pub async fn create_recursive_circuit<G1, G2>(
-    witness_generator_file: FileLocation,
-    r1cs: R1CS<F<G1>>,
-    private_inputs: Vec<HashMap<String, Value>>,
-    start_public_input: Vec<F<G1>>,
-    pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
-) -> Result<RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>, std::io::Error>
-where
-    G1: Group<Base = <G2 as Group>::Scalar>,
-    G2: Group<Base = <G1 as Group>::Scalar>,
-{
-
-    let iteration_count = private_inputs.len();
-
-    let start_public_input_hex = start_public_input
-        .iter()
-        .map(|&x| format!("{:?}", x).strip_prefix("0x").unwrap().to_string())
-        .collect::<Vec<String>>();
-    let mut current_public_input = start_public_input_hex.clone();
-
-    let witness_0 = compute_witness::<G1, G2>(
-        current_public_input.clone(),
-        private_inputs[0].clone(),
-        witness_generator_file.clone(),
-    )
-    .await;
-
-    let circuit_0 = CircomCircuit {
-        r1cs: r1cs.clone(),
-        witness: Some(witness_0),
-    };
-    let circuit_secondary = TrivialTestCircuit::default();
-    let z0_secondary = vec![G2::Scalar::ZERO];
-
-    let mut recursive_snark = RecursiveSNARK::<G1, G2, C1<G1>, C2<G2>>::new(
-        &pp,
-        &circuit_0,
-        &circuit_secondary,
-        start_public_input.clone(),
-        z0_secondary.clone(),
-    );
-
-    for i in 0..iteration_count {
-        let witness = compute_witness::<G1, G2>(
-            current_public_input.clone(),
-            private_inputs[i].clone(),
-            witness_generator_file.clone(),
+prover_fn! {
+    /// Fold witnesses computed ahead of time instead of running a witness generator, with
+    /// `witnesses[i]` the witness of step `i`. Fails if the `step_in` of a witness isn't the
+    /// `step_out` of the previous one, or `start_public_input` for the first.
+    pub fn create_recursive_circuit_from_witnesses<G1, G2>(
+        r1cs: R1CS<F<G1>>,
+        witnesses: Vec<Vec<F<G1>>>,
+        start_public_input: Vec<F<G1>>,
+        pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
+    ) -> Result<RecursiveProof<G1, G2, C1<G1>, C2<G2>>, std::io::Error>
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+    {
+        let num_steps = witnesses.len();
+        let steps = CircomSteps {
+            witness_generator: &PrecomputedWitnesses::new(witnesses),
+            r1cs: &r1cs,
+        };
+        prove::create(
+            &steps,
+            vec![HashMap::<String, Value>::new(); num_steps],
+            start_public_input,
+            vec![TrivialCircuit::default(); num_steps],
+            vec![G2::Scalar::ZERO],
+            pp,
+            &mut (),
+            &CancellationToken::new(),
         )
-        .await;
+        .await
+        .map(ProvingOutcome::into_recursive_proof)
+    }
+}
 
-        let circuit = CircomCircuit {
-            r1cs: r1cs.clone(),
-            witness: Some(witness),
+prover_fn! {
+    /// Like `create_recursive_circuit_from_witnesses`, loading the witness of step `i` from
+    /// `witness_files[i]` (a `.wtns` or JSON file) when the step is folded.
+    pub fn create_recursive_circuit_from_witness_files<G1, G2>(
+        r1cs: R1CS<F<G1>>,
+        witness_files: Vec<PathBuf>,
+        start_public_input: Vec<F<G1>>,
+        pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
+    ) -> Result<RecursiveProof<G1, G2, C1<G1>, C2<G2>>, std::io::Error>
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+    {
+        let num_steps = witness_files.len();
+        let steps = CircomSteps {
+            witness_generator: &WitnessFiles::new(witness_files),
+            r1cs: &r1cs,
         };
-
-        let current_public_output = circuit.get_public_outputs();
-        current_public_input = current_public_output
-            .iter()
-            .map(|&x| format!("{:?}", x).strip_prefix("0x").unwrap().to_string())
-            .collect();
-
-        let res = recursive_snark.prove_step(
-            &pp,
-            &circuit,
-            &circuit_secondary,
-            start_public_input.clone(),
-            z0_secondary.clone(),
-        );
-        assert!(res.is_ok());
+        prove::create(
+            &steps,
+            vec![HashMap::<String, Value>::new(); num_steps],
+            start_public_input,
+            vec![TrivialCircuit::default(); num_steps],
+            vec![G2::Scalar::ZERO],
+            pp,
+            &mut (),
+            &CancellationToken::new(),
+        )
+        .await
+        .map(ProvingOutcome::into_recursive_proof)
     }
-
-    Ok(recursive_snark)
 }
 
-#[cfg(not(target_family = "wasm"))]
-pub fn continue_recursive_circuit<G1, G2>(
-    recursive_snark: &mut RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>,
-    last_zi: Vec<F<G1>>,
-    witness_generator_file: FileLocation,
-    r1cs: R1CS<F<G1>>,
-    private_inputs: Vec<HashMap<String, Value>>,
-    start_public_input: Vec<F<G1>>,
-    pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
-) -> Result<(), std::io::Error>
-where
-    G1: Group<Base = <G2 as Group>::Scalar>,
-    G2: Group<Base = <G1 as Group>::Scalar>,
-{
-    let root = current_dir().unwrap();
-    let witness_generator_output = root.join("circom_witness.wtns");
-
-    let iteration_count = private_inputs.len();
-
-    let mut current_public_input = last_zi
-        .iter()
-        .map(|&x| format!("{:?}", x).strip_prefix("0x").unwrap().to_string())
-        .collect::<Vec<String>>();
-
-    let circuit_secondary = TrivialTestCircuit::default();
-    let z0_secondary = vec![G2::Scalar::ZERO];
-
-    for i in 0..iteration_count {
-        let witness = compute_witness::<G1, G2>(
-            current_public_input.clone(),
-            private_inputs[i].clone(),
-            witness_generator_file.clone(),
-            &witness_generator_output,
-        );
+prover_fn! {
+    /// Fold one more step per private input into `recursive_proof`, continuing from its `z_n`.
+    /// If a step fails, `recursive_proof` is left as it was. Use `ivc::IvcProver::resume` to
+    /// fold steps one at a time instead.
+    pub fn continue_recursive_circuit<G1, G2, I>(
+        recursive_proof: &mut RecursiveProof<G1, G2, C1<G1>, C2<G2>>,
+        witness_generator_file: FileLocation,
+        r1cs: R1CS<F<G1>>,
+        private_inputs: Vec<I>,
+        pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
+    ) -> Result<(), std::io::Error>
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+        I: StepInput,
+    {
+        let private_inputs = to_signals(&private_inputs)?;
+        let witness_generator = default_witness_generator(&witness_generator_file)?;
+        let mut prover = IvcProver::resume(pp, r1cs, witness_generator, recursive_proof.clone());
+        for private_input in private_inputs {
+            prover.fold(private_input).await?;
+        }
+        *recursive_proof = prover.finish()?;
+        Ok(())
+    }
+}
 
-        let circuit = CircomCircuit {
-            r1cs: r1cs.clone(),
-            witness: Some(witness),
+prover_fn! {
+    /// Like `continue_recursive_circuit`, for a `RecursiveProof` created with
+    /// `create_recursive_circuit_with_secondary`. Returns how far proving got if `cancel` was
+    /// cancelled, in which case `recursive_proof` covers the steps folded until then. If a step
+    /// fails, `recursive_proof` covers the steps folded before it, which `observer` has seen.
+    #[allow(clippy::too_many_arguments)]
+    pub fn continue_recursive_circuit_with_secondary<G1, G2, W, SC, O, I>(
+        recursive_proof: &mut RecursiveProof<G1, G2, C1<G1>, SC>,
+        witness_generator: &W,
+        r1cs: R1CS<F<G1>>,
+        private_inputs: Vec<I>,
+        circuits_secondary: Vec<SC>,
+        pp: &PublicParams<G1, G2, C1<G1>, SC>,
+        observer: &mut O,
+        cancel: &CancellationToken,
+    ) -> Result<Option<Cancelled<F<G1>>>, std::io::Error>
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+        W: WitnessGenerator<F<G1>> + ?Sized,
+        SC: StepCircuit<F<G2>>,
+        O: ProgressObserver<F<G1>>,
+        I: StepInput,
+    {
+        let private_inputs = to_signals(&private_inputs)?;
+        let steps = CircomSteps {
+            witness_generator,
+            r1cs: &r1cs,
         };
-
-        let current_public_output = circuit.get_public_outputs();
-        current_public_input = current_public_output
-            .iter()
-            .map(|&x| format!("{:?}", x).strip_prefix("0x").unwrap().to_string())
-            .collect();
-
-        let res = recursive_snark.prove_step(
+        prove::resume(
+            recursive_proof,
+            &steps,
+            private_inputs,
+            circuits_secondary,
             pp,
-            &circuit,
-            &circuit_secondary,
-            start_public_input.clone(),
-            z0_secondary.clone(),
-        );
+            observer,
+            cancel,
+        )
+        .await
+    }
+}
 
-        assert!(res.is_ok());
+prover_fn! {
+    /// Compute the secondary circuits for a Circom step circuit compiled for the scalar field of
+    /// `G2` (i.e. with the other curve's `--prime`), chaining its `step_out` into the next
+    /// `step_in` starting from `z0_secondary`. The result can be passed as `circuits_secondary`
+    /// to `create_recursive_circuit_with_secondary`.
+    pub fn create_secondary_circom_circuits<G1, G2, W, I>(
+        witness_generator: &W,
+        r1cs: R1CS<F<G2>>,
+        private_inputs: Vec<I>,
+        z0_secondary: Vec<F<G2>>,
+    ) -> Result<Vec<C1<G2>>, std::io::Error>
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+        W: WitnessGenerator<F<G2>> + ?Sized,
+        I: StepInput,
+    {
+        let private_inputs = to_signals(&private_inputs)?;
+        prove::secondary_circom_circuits(witness_generator, r1cs, private_inputs, z0_secondary)
+            .await
     }
+}
 
-    fs::remove_file(witness_generator_output)?;
+prover_fn! {
+    /// Like `create_recursive_circuit_with_secondary`, for a step circuit composed of Circom
+    /// stages and gadgets. `circuit` is the witness-free `ComposedCircuit` that `pp` was set up
+    /// with, and `private_inputs[i]` holds the private inputs of each Circom stage at step `i`.
+    #[allow(clippy::type_complexity)]
+    pub fn create_recursive_circuit_composed<G1, G2, G, O>(
+        circuit: ComposedCircuit<F<G1>, G>,
+        private_inputs: Vec<Vec<HashMap<String, Value>>>,
+        start_public_input: Vec<F<G1>>,
+        pp: &PublicParams<G1, G2, ComposedCircuit<F<G1>, G>, C2<G2>>,
+        observer: &mut O,
+        cancel: &CancellationToken,
+    ) -> Result<
+        ProvingOutcome<RecursiveProof<G1, G2, ComposedCircuit<F<G1>, G>, C2<G2>>, F<G1>>,
+        std::io::Error,
+    >
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+        G: StepCircuit<F<G1>>,
+        O: ProgressObserver<F<G1>>,
+    {
+        let steps = ComposedSteps::new(&circuit)?;
+        let num_steps = private_inputs.len();
+        prove::create(
+            &steps,
+            private_inputs,
+            start_public_input,
+            vec![TrivialCircuit::default(); num_steps],
+            vec![G2::Scalar::ZERO],
+            pp,
+            observer,
+            cancel,
+        )
+        .await
+    }
+}
 
-    Ok(())
+prover_fn! {
+    /// Like `continue_recursive_circuit_with_secondary`, for a `RecursiveProof` created with
+    /// `create_recursive_circuit_composed`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn continue_recursive_circuit_composed<G1, G2, G, O>(
+        recursive_proof: &mut RecursiveProof<G1, G2, ComposedCircuit<F<G1>, G>, C2<G2>>,
+        circuit: ComposedCircuit<F<G1>, G>,
+        private_inputs: Vec<Vec<HashMap<String, Value>>>,
+        pp: &PublicParams<G1, G2, ComposedCircuit<F<G1>, G>, C2<G2>>,
+        observer: &mut O,
+        cancel: &CancellationToken,
+    ) -> Result<Option<Cancelled<F<G1>>>, std::io::Error>
+    where
+        G1: Group<Base = <G2 as Group>::Scalar>,
+        G2: Group<Base = <G1 as Group>::Scalar>,
+        G: StepCircuit<F<G1>>,
+        O: ProgressObserver<F<G1>>,
+    {
+        let steps = ComposedSteps::new(&circuit)?;
+        let num_steps = private_inputs.len();
+        prove::resume(
+            recursive_proof,
+            &steps,
+            private_inputs,
+            vec![TrivialCircuit::default(); num_steps],
+            pp,
+            observer,
+            cancel,
+        )
+        .await
+    }
 }
 
-#[cfg(target_family = "wasm")]
-pub async fn continue_recursive_circuit<G1, G2>(
-    recursive_snark: &mut RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>,
-    last_zi: Vec<F<G1>>,
-    witness_generator_file: FileLocation,
-    r1cs: R1CS<F<G1>>,
-    private_inputs: Vec<HashMap<String, Value>>,
-    start_public_input: Vec<F<G1>>,
-    pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
-) -> Result<(), std::io::Error>
+/// Compress a `RecursiveSNARK` into a `CompressedSNARK`, within a `compress` tracing span.
+///
+/// Hiding is not supported: the result is not zero-knowledge, since nova-snark 0.24 neither
+/// blinds the folded instance nor uses hiding commitments, so it may reveal information about the
+/// private inputs.
+#[cfg(feature = "prover")]
+#[tracing::instrument(name = "compress", skip_all)]
+pub fn compress_recursive_snark<G1, G2, SC1, SC2, S1, S2>(
+    pp: &PublicParams<G1, G2, SC1, SC2>,
+    pk: &ProverKey<G1, G2, SC1, SC2, S1, S2>,
+    recursive_snark: &RecursiveSNARK<G1, G2, SC1, SC2>,
+) -> Result<CompressedSNARK<G1, G2, SC1, SC2, S1, S2>, NovaError>
 where
     G1: Group<Base = <G2 as Group>::Scalar>,
     G2: Group<Base = <G1 as Group>::Scalar>,
+    SC1: StepCircuit<F<G1>>,
+    SC2: StepCircuit<F<G2>>,
+    S1: RelaxedR1CSSNARKTrait<G1>,
+    S2: RelaxedR1CSSNARKTrait<G2>,
 {
-    let root = current_dir().unwrap();
-    let witness_generator_output = root.join("circom_witness.wtns");
-
-    let iteration_count = private_inputs.len();
-
-    let mut current_public_input = last_zi
-        .iter()
-        .map(|&x| format!("{:?}", x).strip_prefix("0x").unwrap().to_string())
-        .collect::<Vec<String>>();
-
-    let circuit_secondary = TrivialTestCircuit::default();
-    let z0_secondary = vec![G2::Scalar::ZERO];
-
-    for i in 0..iteration_count {
-        let witness = compute_witness::<G1, G2>(
-            current_public_input.clone(),
-            private_inputs[i].clone(),
-            witness_generator_file.clone(),
-        )
-        .await;
-
-        let circuit = CircomCircuit {
-            r1cs: r1cs.clone(),
-            witness: Some(witness),
-        };
-
-        let current_public_output = circuit.get_public_outputs();
-        current_public_input = current_public_output
-            .iter()
-            .map(|&x| format!("{:?}", x).strip_prefix("0x").unwrap().to_string())
-            .collect();
-
-        let res = recursive_snark.prove_step(
-            pp,
-            &circuit,
-            &circuit_secondary,
-            start_public_input.clone(),
-            z0_secondary.clone(),
-        );
-
-        assert!(res.is_ok());
-    }
-
-    fs::remove_file(witness_generator_output)?;
-
-    Ok(())
+    CompressedSNARK::prove(pp, pk, recursive_snark)
 }
@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+/// Progress of the proving loop after folding one step.
+pub struct StepProgress<'a, Fr> {
+    /// Index of the step in the proof, starting from 0 and continuing from the steps already
+    /// folded when resuming with a `continue_` function.
+    pub step: usize,
+    /// Time taken to compute the witness(es) of the step.
+    pub witness_generation_time: Duration,
+    /// Time taken by `RecursiveSNARK::prove_step`.
+    pub fold_time: Duration,
+    /// The primary `z_i` after folding the step.
+    pub z_i: &'a [Fr],
+    /// The primary `z_i` the step started from.
+    pub step_in: &'a [Fr],
+    /// The step's private input, as given to the witness generator.
+    pub private_input: &'a Value,
+    /// The witness of every Circom circuit in the step, in order.
+    pub witnesses: &'a [&'a [Fr]],
+}
+
+/// Observer called by the proving loops after every step, e.g. to report progress or estimate
+/// the remaining proving time. Implemented for closures taking a `&StepProgress`, and for `()`,
+/// which ignores all progress.
+pub trait ProgressObserver<Fr> {
+    fn on_step(&mut self, progress: &StepProgress<Fr>);
+}
+
+impl<Fr> ProgressObserver<Fr> for () {
+    fn on_step(&mut self, _progress: &StepProgress<Fr>) {}
+}
+
+impl<Fr, T: FnMut(&StepProgress<Fr>)> ProgressObserver<Fr> for T {
+    fn on_step(&mut self, progress: &StepProgress<Fr>) {
+        self(progress)
+    }
+}
+
+/// `std::time::Instant` is not available in browsers, so fall back to `Date.now()` there.
+pub(crate) struct Stopwatch {
+    #[cfg(not(target_family = "wasm"))]
+    start: std::time::Instant,
+    #[cfg(target_family = "wasm")]
+    start: f64,
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(not(target_family = "wasm"))]
+            start: std::time::Instant::now(),
+            #[cfg(target_family = "wasm")]
+            start: js_sys::Date::now(),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(not(target_family = "wasm"))]
+        return self.start.elapsed();
+        #[cfg(target_family = "wasm")]
+        return Duration::from_secs_f64((js_sys::Date::now() - self.start) / 1000.0);
+    }
+}
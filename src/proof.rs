@@ -0,0 +1,101 @@
+use nova_snark::{
+    errors::NovaError,
+    traits::{circuit::StepCircuit, Group},
+    PublicParams, RecursiveSNARK,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::F;
+
+/// A `RecursiveSNARK` along with what it takes to verify it: the initial inputs of both
+/// circuits and the number of folded steps. Returned by the `create_` proving functions, and
+/// kept up to date by the `continue_` ones.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecursiveProof<G1, G2, SC1, SC2>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    SC1: StepCircuit<F<G1>>,
+    SC2: StepCircuit<F<G2>>,
+{
+    pub(crate) recursive_snark: RecursiveSNARK<G1, G2, SC1, SC2>,
+    pub(crate) z0_primary: Vec<F<G1>>,
+    pub(crate) z0_secondary: Vec<F<G2>>,
+    pub(crate) num_steps: usize,
+    pub(crate) z_n: Vec<F<G1>>,
+}
+
+impl<G1, G2, SC1, SC2> RecursiveProof<G1, G2, SC1, SC2>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    SC1: StepCircuit<F<G1>>,
+    SC2: StepCircuit<F<G2>>,
+{
+    #[cfg(feature = "prover")]
+    pub(crate) fn new(
+        recursive_snark: RecursiveSNARK<G1, G2, SC1, SC2>,
+        z0_primary: Vec<F<G1>>,
+        z0_secondary: Vec<F<G2>>,
+        num_steps: usize,
+        z_n: Vec<F<G1>>,
+    ) -> Self {
+        Self {
+            recursive_snark,
+            z0_primary,
+            z0_secondary,
+            num_steps,
+            z_n,
+        }
+    }
+
+    /// Verify every folded step from `z0_primary`, returning the primary `z_n`.
+    ///
+    /// **This only checks that the proof is consistent with the `z0_primary` and `num_steps` it
+    /// carries.** Whoever built it chose those, so to check a proof received from someone else,
+    /// use `verify_from` with the initial input and number of steps you expect.
+    pub fn verify(&self, pp: &PublicParams<G1, G2, SC1, SC2>) -> Result<Vec<F<G1>>, NovaError> {
+        self.verify_from(pp, &self.z0_primary, self.num_steps)
+    }
+
+    /// Verify that `num_steps` steps from `z0_primary` were folded, returning the primary `z_n`.
+    /// The secondary circuit's initial input is the proof's `z0_secondary`.
+    pub fn verify_from(
+        &self,
+        pp: &PublicParams<G1, G2, SC1, SC2>,
+        z0_primary: &[F<G1>],
+        num_steps: usize,
+    ) -> Result<Vec<F<G1>>, NovaError> {
+        let (z_n, _) =
+            self.recursive_snark
+                .verify(pp, num_steps, z0_primary, &self.z0_secondary)?;
+        Ok(z_n)
+    }
+
+    pub fn recursive_snark(&self) -> &RecursiveSNARK<G1, G2, SC1, SC2> {
+        &self.recursive_snark
+    }
+
+    pub fn into_recursive_snark(self) -> RecursiveSNARK<G1, G2, SC1, SC2> {
+        self.recursive_snark
+    }
+
+    pub fn z0_primary(&self) -> &[F<G1>] {
+        &self.z0_primary
+    }
+
+    pub fn z0_secondary(&self) -> &[F<G2>] {
+        &self.z0_secondary
+    }
+
+    /// Number of steps folded so far, over every proving call.
+    pub fn num_steps(&self) -> usize {
+        self.num_steps
+    }
+
+    /// The primary `step_out` of the last folded step, which proving continues from.
+    pub fn z_n(&self) -> &[F<G1>] {
+        &self.z_n
+    }
+}
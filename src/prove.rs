@@ -0,0 +1,686 @@
+//! The folding loop shared by the native and wasm proving functions. It is written once as
+//! `async` code: on wasm the public functions `.await` it, natively they run it with `block_on`,
+//! where it never has to wait since native witness generators are blocking.
+
+use std::collections::HashMap;
+#[cfg(not(target_family = "wasm"))]
+use std::future::Future;
+use std::io::{self, ErrorKind};
+#[cfg(not(target_family = "wasm"))]
+use std::{env::current_dir, path::PathBuf};
+
+use ff::PrimeField;
+use nova_snark::{
+    traits::{circuit::StepCircuit, Group},
+    PublicParams, RecursiveSNARK,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cancel::{
+    cancelled_error, proving_outcome, CancellationToken, Cancelled, ProvingOutcome,
+};
+use crate::circom::circuit::{CircomCircuit, R1CS};
+use crate::circom::reader::{check_witness_len, WitnessGeneratorOptions};
+use crate::compose::{ComposedCircuit, Stage};
+use crate::progress::{ProgressObserver, StepProgress, Stopwatch};
+use crate::proof::RecursiveProof;
+use crate::witness::{file_witness_generator, CircomInput, WitnessGenerator};
+use crate::F;
+
+/// Compute the witness of `step` with input `z_i`, checking that it fits `r1cs`, was computed
+/// for `z_i` and satisfies the circuit.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn compute_witness<Fr, W>(
+    witness_generator: &W,
+    step: usize,
+    z_i: &[Fr],
+    private_input: HashMap<String, Value>,
+    r1cs: &R1CS<Fr>,
+    cancel: &CancellationToken,
+) -> Result<Vec<Fr>, io::Error>
+where
+    Fr: PrimeField,
+    W: WitnessGenerator<Fr> + ?Sized,
+{
+    // witness generation in the browser can't be interrupted, so this is the only check there
+    if cancel.is_cancelled() {
+        return Err(cancelled_error());
+    }
+
+    let input = CircomInput::new(z_i, private_input);
+    #[cfg(not(target_family = "wasm"))]
+    let witness = witness_generator.generate(&input, cancel)?;
+    #[cfg(target_family = "wasm")]
+    let witness = witness_generator.generate(&input, cancel).await?;
+
+    check_witness_len(&witness, &WitnessGeneratorOptions::for_r1cs(r1cs))?;
+    check_step_witness(step, z_i, r1cs, &witness)?;
+    Ok(witness)
+}
+
+/// Check that `witness` was computed with `z_i` as its `step_in` (e.g. that a precomputed witness
+/// continues from the `step_out` of the previous one), and that its `step_out` really is what the
+/// circuit outputs for it, before `prove_step` fails on it with an opaque error.
+fn check_step_witness<Fr: PrimeField>(
+    step: usize,
+    z_i: &[Fr],
+    r1cs: &R1CS<Fr>,
+    witness: &[Fr],
+) -> Result<(), io::Error> {
+    let step_in = r1cs.public_inputs(witness);
+    if let Some(index) = (0..z_i.len()).find(|&i| step_in.get(i) != Some(&z_i[i])) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "witness of step {} has step_in[{}] = {:?}, expected {:?}",
+                step,
+                index,
+                step_in.get(index),
+                z_i[index]
+            ),
+        ));
+    }
+
+    if let Some(constraint) = r1cs.first_unsatisfied_constraint(witness) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "witness of step {} does not satisfy constraint {}, so its step_out {:?} is not \
+                 the circuit's output",
+                step,
+                constraint,
+                r1cs.public_outputs(witness)
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Produces the primary step circuit, with its witness, of every step folded by `create` and
+/// `resume`.
+pub(crate) trait StepSource<Fr: PrimeField> {
+    /// The private input of one step.
+    type Input: Serialize;
+    type Circuit: StepCircuit<Fr>;
+
+    async fn step_circuit(
+        &self,
+        step: usize,
+        z_i: &[Fr],
+        input: Self::Input,
+        cancel: &CancellationToken,
+    ) -> Result<Self::Circuit, io::Error>;
+
+    /// The `z_{i+1}` of a step circuit returned by `step_circuit` for `z_i`.
+    fn step_out(&self, circuit: &Self::Circuit, z_i: &[Fr]) -> Result<Vec<Fr>, io::Error>;
+
+    /// The witnesses of the Circom circuits in a step circuit returned by `step_circuit`.
+    fn witnesses<'c>(&self, circuit: &'c Self::Circuit) -> Vec<&'c [Fr]>;
+}
+
+/// Steps of a Circom circuit, with witnesses from `witness_generator`.
+pub(crate) struct CircomSteps<'a, Fr: PrimeField, W: ?Sized> {
+    pub witness_generator: &'a W,
+    pub r1cs: &'a R1CS<Fr>,
+}
+
+impl<Fr, W> StepSource<Fr> for CircomSteps<'_, Fr, W>
+where
+    Fr: PrimeField,
+    W: WitnessGenerator<Fr> + ?Sized,
+{
+    type Input = HashMap<String, Value>;
+    type Circuit = CircomCircuit<Fr>;
+
+    async fn step_circuit(
+        &self,
+        step: usize,
+        z_i: &[Fr],
+        input: Self::Input,
+        cancel: &CancellationToken,
+    ) -> Result<Self::Circuit, io::Error> {
+        let witness =
+            compute_witness(self.witness_generator, step, z_i, input, self.r1cs, cancel).await?;
+        Ok(CircomCircuit {
+            r1cs: self.r1cs.clone(),
+            witness: Some(witness),
+        })
+    }
+
+    fn step_out(&self, circuit: &Self::Circuit, _z_i: &[Fr]) -> Result<Vec<Fr>, io::Error> {
+        Ok(circuit.get_public_outputs())
+    }
+
+    fn witnesses<'c>(&self, circuit: &'c Self::Circuit) -> Vec<&'c [Fr]> {
+        circuit.witness.as_deref().into_iter().collect()
+    }
+}
+
+/// Steps of a `ComposedCircuit`, with the witnesses of its Circom stages computed in order.
+pub(crate) struct ComposedSteps<'a, Fr: PrimeField, G: StepCircuit<Fr>> {
+    pub circuit: &'a ComposedCircuit<Fr, G>,
+    #[cfg(not(target_family = "wasm"))]
+    pub witness_generator_output: PathBuf,
+}
+
+impl<'a, Fr: PrimeField, G: StepCircuit<Fr>> ComposedSteps<'a, Fr, G> {
+    /// Natively, Circom stages write their witnesses to `circom_witness.wtns` in the working
+    /// directory.
+    pub fn new(circuit: &'a ComposedCircuit<Fr, G>) -> Result<Self, io::Error> {
+        Ok(Self {
+            circuit,
+            #[cfg(not(target_family = "wasm"))]
+            witness_generator_output: current_dir()?.join("circom_witness.wtns"),
+        })
+    }
+}
+
+impl<Fr, G> StepSource<Fr> for ComposedSteps<'_, Fr, G>
+where
+    Fr: PrimeField,
+    G: StepCircuit<Fr>,
+{
+    type Input = Vec<HashMap<String, Value>>;
+    type Circuit = ComposedCircuit<Fr, G>;
+
+    async fn step_circuit(
+        &self,
+        step: usize,
+        z_i: &[Fr],
+        input: Self::Input,
+        cancel: &CancellationToken,
+    ) -> Result<Self::Circuit, io::Error> {
+        #[cfg(not(target_family = "wasm"))]
+        let witness_generator = |file: &_| {
+            Ok(file_witness_generator(
+                file,
+                self.witness_generator_output.clone(),
+            ))
+        };
+        #[cfg(target_family = "wasm")]
+        let witness_generator = file_witness_generator;
+
+        self.circuit
+            .witness_stages(step, z_i, input, witness_generator, cancel)
+            .await
+    }
+
+    fn step_out(&self, circuit: &Self::Circuit, z_i: &[Fr]) -> Result<Vec<Fr>, io::Error> {
+        circuit.output(z_i).map_err(io::Error::other)
+    }
+
+    fn witnesses<'c>(&self, circuit: &'c Self::Circuit) -> Vec<&'c [Fr]> {
+        circuit
+            .stages
+            .iter()
+            .filter_map(|stage| match stage {
+                Stage::Circom { circuit, .. } => circuit.witness.as_deref(),
+                Stage::Gadget(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Fold one step per private input into `recursive_snark`, or into a new `RecursiveSNARK`
+/// created at the first step if it is `None`, until `cancel` is cancelled. `num_steps` and `z_i`
+/// are the number of steps `recursive_snark` already covers and their output, and are advanced
+/// after every folded step, so that if a step fails they describe the steps folded before it.
+/// Returns the `RecursiveSNARK` created at the first step, if any.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+async fn fold_steps<G1, G2, P, SC, O>(
+    mut recursive_snark: Option<&mut RecursiveSNARK<G1, G2, P::Circuit, SC>>,
+    steps: &P,
+    num_steps: &mut usize,
+    z_i: &mut Vec<F<G1>>,
+    private_inputs: Vec<P::Input>,
+    start_public_input: &[F<G1>],
+    circuits_secondary: Vec<SC>,
+    z0_secondary: &[F<G2>],
+    pp: &PublicParams<G1, G2, P::Circuit, SC>,
+    observer: &mut O,
+    cancel: &CancellationToken,
+) -> Result<Option<RecursiveSNARK<G1, G2, P::Circuit, SC>>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    P: StepSource<F<G1>>,
+    SC: StepCircuit<F<G2>>,
+    O: ProgressObserver<F<G1>>,
+{
+    if private_inputs.len() != circuits_secondary.len() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "{} private inputs, but {} secondary circuits",
+                private_inputs.len(),
+                circuits_secondary.len()
+            ),
+        ));
+    }
+
+    let mut created = None;
+
+    for (private_input, circuit_secondary) in private_inputs.into_iter().zip(circuits_secondary) {
+        if cancel.is_cancelled() {
+            break;
+        }
+
+        let step = *num_steps;
+        let private_input_json = serde_json::to_value(&private_input)?;
+        let stopwatch = Stopwatch::start();
+        let circuit = match steps.step_circuit(step, z_i, private_input, cancel).await {
+            // an Interrupted error from e.g. an EINTR is a failure unless we asked for it
+            Err(_) if cancel.is_cancelled() => break,
+            circuit => circuit?,
+        };
+        // before folding, so that a folded step is always counted
+        let step_out = steps.step_out(&circuit, z_i)?;
+        let witness_generation_time = stopwatch.elapsed();
+
+        let stopwatch = Stopwatch::start();
+        let res = tracing::info_span!("prove_step", step).in_scope(|| {
+            let recursive_snark = match recursive_snark.as_deref_mut() {
+                Some(recursive_snark) => recursive_snark,
+                None => created.get_or_insert_with(|| {
+                    RecursiveSNARK::new(
+                        pp,
+                        &circuit,
+                        &circuit_secondary,
+                        start_public_input.to_vec(),
+                        z0_secondary.to_vec(),
+                    )
+                }),
+            };
+            recursive_snark.prove_step(
+                pp,
+                &circuit,
+                &circuit_secondary,
+                start_public_input.to_vec(),
+                z0_secondary.to_vec(),
+            )
+        });
+        let fold_time = stopwatch.elapsed();
+        res.map_err(|err| io::Error::other(format!("step {} failed to fold: {:?}", step, err)))?;
+
+        *num_steps += 1;
+        observer.on_step(&StepProgress {
+            step,
+            witness_generation_time,
+            fold_time,
+            z_i: &step_out,
+            step_in: z_i,
+            private_input: &private_input_json,
+            witnesses: &steps.witnesses(&circuit),
+        });
+        *z_i = step_out;
+    }
+
+    Ok(created)
+}
+
+/// Create a `RecursiveProof` folding one step per private input, starting from
+/// `start_public_input`.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) async fn create<G1, G2, P, SC, O>(
+    steps: &P,
+    private_inputs: Vec<P::Input>,
+    start_public_input: Vec<F<G1>>,
+    circuits_secondary: Vec<SC>,
+    z0_secondary: Vec<F<G2>>,
+    pp: &PublicParams<G1, G2, P::Circuit, SC>,
+    observer: &mut O,
+    cancel: &CancellationToken,
+) -> Result<ProvingOutcome<RecursiveProof<G1, G2, P::Circuit, SC>, F<G1>>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    P: StepSource<F<G1>>,
+    SC: StepCircuit<F<G2>>,
+    O: ProgressObserver<F<G1>>,
+{
+    let total_steps = private_inputs.len();
+    let mut num_steps = 0;
+    let mut z_i = start_public_input.clone();
+    let recursive_snark = fold_steps(
+        None,
+        steps,
+        &mut num_steps,
+        &mut z_i,
+        private_inputs,
+        &start_public_input,
+        circuits_secondary,
+        &z0_secondary,
+        pp,
+        observer,
+        cancel,
+    )
+    .await?;
+
+    let recursive_proof = recursive_snark.map(|recursive_snark| {
+        RecursiveProof::new(
+            recursive_snark,
+            start_public_input,
+            z0_secondary,
+            num_steps,
+            z_i.clone(),
+        )
+    });
+    proving_outcome(recursive_proof, num_steps, total_steps, z_i)
+}
+
+/// Fold one more step per private input into `recursive_proof`, continuing from its `z_n`. It is
+/// updated in place after every folded step, so if a step fails it covers the steps folded (and
+/// seen by `observer`) before that one.
+pub(crate) async fn resume<G1, G2, P, SC, O>(
+    recursive_proof: &mut RecursiveProof<G1, G2, P::Circuit, SC>,
+    steps: &P,
+    private_inputs: Vec<P::Input>,
+    circuits_secondary: Vec<SC>,
+    pp: &PublicParams<G1, G2, P::Circuit, SC>,
+    observer: &mut O,
+    cancel: &CancellationToken,
+) -> Result<Option<Cancelled<F<G1>>>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    P: StepSource<F<G1>>,
+    SC: StepCircuit<F<G2>>,
+    O: ProgressObserver<F<G1>>,
+{
+    let total_steps = private_inputs.len();
+    let first_step = recursive_proof.num_steps;
+    let RecursiveProof {
+        recursive_snark,
+        z0_primary,
+        z0_secondary,
+        num_steps,
+        z_n,
+    } = recursive_proof;
+    fold_steps(
+        Some(recursive_snark),
+        steps,
+        num_steps,
+        z_n,
+        private_inputs,
+        z0_primary,
+        circuits_secondary,
+        z0_secondary,
+        pp,
+        observer,
+        cancel,
+    )
+    .await?;
+
+    let num_steps = recursive_proof.num_steps - first_step;
+    Ok((num_steps < total_steps).then(|| Cancelled {
+        num_steps,
+        z_i: recursive_proof.z_n.clone(),
+    }))
+}
+
+/// Compute the secondary Circom circuits of every step, chaining `step_out` into the next
+/// `step_in` starting from `z0_secondary`.
+pub(crate) async fn secondary_circom_circuits<Fr, W>(
+    witness_generator: &W,
+    r1cs: R1CS<Fr>,
+    private_inputs: Vec<HashMap<String, Value>>,
+    z0_secondary: Vec<Fr>,
+) -> Result<Vec<CircomCircuit<Fr>>, io::Error>
+where
+    Fr: PrimeField,
+    W: WitnessGenerator<Fr> + ?Sized,
+{
+    let steps = CircomSteps {
+        witness_generator,
+        r1cs: &r1cs,
+    };
+    let cancel = CancellationToken::new();
+    let mut z = z0_secondary;
+    let mut circuits = Vec::with_capacity(private_inputs.len());
+
+    for (i, private_input) in private_inputs.into_iter().enumerate() {
+        let circuit = steps.step_circuit(i, &z, private_input, &cancel).await?;
+        z = steps.step_out(&circuit, &z)?;
+        circuits.push(circuit);
+    }
+
+    Ok(circuits)
+}
+
+/// Run a future to completion on the current thread.
+#[cfg(not(target_family = "wasm"))]
+pub(crate) fn block_on<T>(future: impl Future<Output = T>) -> T {
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(value) => return value,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn fold_steps_across_await_points() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::witness::{NodeWitnessGenerator, PrecomputedWitnesses};
+        use crate::{create_public_params, FileLocation};
+        use ff::Field;
+        use nova_snark::traits::circuit::TrivialCircuit;
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as Group>::Scalar;
+        type Fq = <G2 as Group>::Scalar;
+
+        /// Yields to the executor once before completing, like a browser witness generator.
+        struct YieldOnce(bool);
+
+        impl Future for YieldOnce {
+            type Output = ();
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                if self.0 {
+                    return Poll::Ready(());
+                }
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+
+        /// Circom steps whose witnesses take a trip through the executor.
+        struct Yielding<'a>(CircomSteps<'a, Fr, PrecomputedWitnesses<Fr>>);
+
+        impl StepSource<Fr> for Yielding<'_> {
+            type Input = HashMap<String, Value>;
+            type Circuit = CircomCircuit<Fr>;
+
+            async fn step_circuit(
+                &self,
+                step: usize,
+                z_i: &[Fr],
+                input: Self::Input,
+                cancel: &CancellationToken,
+            ) -> Result<Self::Circuit, io::Error> {
+                YieldOnce(false).await;
+                self.0.step_circuit(step, z_i, input, cancel).await
+            }
+
+            fn step_out(&self, circuit: &Self::Circuit, z_i: &[Fr]) -> Result<Vec<Fr>, io::Error> {
+                self.0.step_out(circuit, z_i)
+            }
+
+            fn witnesses<'c>(&self, circuit: &'c Self::Circuit) -> Vec<&'c [Fr]> {
+                self.0.witnesses(circuit)
+            }
+        }
+
+        let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            root.join("examples/toy/pasta/toy.r1cs"),
+        ));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+
+        // (10, 10) -> (10, 20) -> (11, 30) -> (13, 41) -> (16, 54)
+        let witness_generator = NodeWitnessGenerator {
+            witness_output: root.join("fold_steps_across_await_points.wtns"),
+            ..NodeWitnessGenerator::new(root.join("examples/toy/pasta/toy_js/toy.wasm")).unwrap()
+        };
+        let private_inputs = (0..4)
+            .map(|adder| HashMap::from([("adder".to_string(), serde_json::json!(adder))]))
+            .collect::<Vec<_>>();
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+        let witnesses = block_on(secondary_circom_circuits(
+            &witness_generator,
+            r1cs.clone(),
+            private_inputs,
+            z0.clone(),
+        ))
+        .unwrap()
+        .into_iter()
+        .map(|circuit| circuit.witness.unwrap())
+        .collect::<Vec<_>>();
+
+        let precomputed = PrecomputedWitnesses::new(witnesses[..2].to_vec());
+        let steps = Yielding(CircomSteps {
+            witness_generator: &precomputed,
+            r1cs: &r1cs,
+        });
+        let err = block_on(create(
+            &steps,
+            vec![HashMap::new(); 2],
+            z0.clone(),
+            vec![TrivialCircuit::default(); 1],
+            vec![Fq::ZERO],
+            &pp,
+            &mut (),
+            &CancellationToken::new(),
+        ))
+        .err()
+        .unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let mut recursive_proof = block_on(create(
+            &steps,
+            vec![HashMap::new(); 2],
+            z0.clone(),
+            vec![TrivialCircuit::default(); 2],
+            vec![Fq::ZERO],
+            &pp,
+            &mut (),
+            &CancellationToken::new(),
+        ))
+        .unwrap()
+        .into_recursive_proof();
+        let z_i = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(z_i, vec![Fr::from(11), Fr::from(30)]);
+
+        // a step failing after another was folded keeps the step before it
+        let mut corrupted = witnesses[2..].to_vec();
+        corrupted[1][2] += Fr::ONE;
+        let precomputed = PrecomputedWitnesses::new(corrupted);
+        let steps = Yielding(CircomSteps {
+            witness_generator: &precomputed,
+            r1cs: &r1cs,
+        });
+        let mut folded_steps = vec![];
+        let err = block_on(resume(
+            &mut recursive_proof,
+            &steps,
+            vec![HashMap::new(); 2],
+            vec![TrivialCircuit::default(); 2],
+            &pp,
+            &mut |progress: &StepProgress<Fr>| folded_steps.push(progress.step),
+            &CancellationToken::new(),
+        ))
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("witness of step 3 does not satisfy constraint"));
+        assert_eq!(folded_steps, vec![2]);
+        assert_eq!(recursive_proof.num_steps(), 3);
+        assert_eq!(recursive_proof.z_n(), [Fr::from(13), Fr::from(41)]);
+        let z_i = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(z_i, vec![Fr::from(13), Fr::from(41)]);
+
+        let precomputed = PrecomputedWitnesses::new(witnesses[3..].to_vec());
+        let steps = Yielding(CircomSteps {
+            witness_generator: &precomputed,
+            r1cs: &r1cs,
+        });
+        // steps are numbered on from those already folded
+        let mut folded_steps = vec![];
+        let cancelled = block_on(resume(
+            &mut recursive_proof,
+            &steps,
+            vec![HashMap::new(); 1],
+            vec![TrivialCircuit::default(); 1],
+            &pp,
+            &mut |progress: &StepProgress<Fr>| folded_steps.push(progress.step),
+            &CancellationToken::new(),
+        ))
+        .unwrap();
+        assert_eq!(cancelled, None);
+        assert_eq!(folded_steps, vec![3]);
+        assert_eq!(recursive_proof.num_steps(), 4);
+        let z_i = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(z_i, vec![Fr::from(16), Fr::from(54)]);
+    }
+
+    #[cfg(all(test, target_family = "wasm"))]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    async fn fold_steps_on_wasm() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::{create_public_params, create_recursive_circuit_from_witnesses, FileLocation};
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as Group>::Scalar;
+
+        // wasm-bindgen-rayon's workers are started from JavaScript, so fold on this thread
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .use_current_thread()
+            .build_global()
+            .unwrap();
+
+        let r1cs_bytes = include_bytes!("../examples/toy/pasta/toy.r1cs");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::Bytes(r1cs_bytes.as_slice().into())).await;
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+
+        // (10, 10) -> (10, 20) -> (11, 30)
+        let witnesses = [[1, 10, 20, 10, 10], [1, 11, 30, 10, 20]]
+            .iter()
+            .map(|witness| witness.iter().map(|&x| Fr::from(x)).collect())
+            .collect();
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+        let recursive_proof = create_recursive_circuit_from_witnesses(r1cs, witnesses, z0, &pp)
+            .await
+            .unwrap();
+        let z_i = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(z_i, vec![Fr::from(11), Fr::from(30)]);
+    }
+}
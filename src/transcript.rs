@@ -0,0 +1,281 @@
+//! A record of every folded step, for auditing what was proven without rerunning the prover.
+
+use std::io::{self, BufRead, Write};
+
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::export::to_decimal_strings;
+use crate::progress::{ProgressObserver, StepProgress};
+
+/// One folded step, as written to a line of a JSONL transcript.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    /// Index of the step in the proof, continuing from the steps already folded when proving
+    /// was resumed.
+    pub step: usize,
+    /// The primary `z_i` the step started from, in decimal.
+    pub step_in: Vec<String>,
+    /// The primary `z_{i+1}` the step output, in decimal.
+    pub step_out: Vec<String>,
+    /// Hex SHA-256 of the private input serialized as compact JSON, with the keys of every object
+    /// sorted.
+    pub private_input_sha256: String,
+    /// Hex SHA-256 of the step's witnesses, each element as its 32-byte little-endian
+    /// representation, in the order of a `.wtns` file.
+    pub witness_sha256: String,
+    pub witness_generation_ms: u128,
+    pub fold_ms: u128,
+}
+
+/// A `ProgressObserver` recording a `TranscriptEntry` per folded step.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transcript {
+    pub entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write one JSON entry per line.
+    pub fn write_jsonl<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        for entry in &self.entries {
+            serde_json::to_writer(&mut writer, entry)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+
+    /// Read a transcript written by `write_jsonl`, skipping blank lines.
+    pub fn read_jsonl<R: BufRead>(reader: R) -> Result<Self, io::Error> {
+        let mut entries = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                entries.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(Self { entries })
+    }
+}
+
+impl<Fr: PrimeField> ProgressObserver<Fr> for Transcript {
+    fn on_step(&mut self, progress: &StepProgress<Fr>) {
+        let private_input = serde_json::to_vec(&sort_keys(progress.private_input)).unwrap();
+        let mut witness = Sha256::new();
+        for value in progress.witnesses.iter().flat_map(|witness| witness.iter()) {
+            witness.update(value.to_repr());
+        }
+
+        self.entries.push(TranscriptEntry {
+            step: progress.step,
+            step_in: to_decimal_strings(progress.step_in),
+            step_out: to_decimal_strings(progress.z_i),
+            private_input_sha256: to_hex(&Sha256::digest(private_input)),
+            witness_sha256: to_hex(&witness.finalize()),
+            witness_generation_ms: progress.witness_generation_time.as_millis(),
+            fold_ms: progress.fold_time.as_millis(),
+        });
+    }
+}
+
+/// `value` with the keys of every object inserted in sorted order, so that it serializes the
+/// same whether or not serde_json's `preserve_order` feature is enabled by another crate.
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries = object.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|(key, _)| *key);
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), sort_keys(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.iter().map(sort_keys).collect()),
+        value => value.clone(),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn record_steps_as_jsonl() {
+        use super::*;
+        use crate::cancel::{CancellationToken, ProvingOutcome};
+        use crate::circom::reader::load_r1cs;
+        use crate::witness::{CircomInput, NodeWitnessGenerator, WitnessGenerator};
+        use crate::{
+            create_public_params, create_recursive_circuit_with_secondary, FileLocation, C2, F,
+        };
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = F<G1>;
+        type Fq = F<G2>;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta");
+        let witness_generator = NodeWitnessGenerator {
+            witness_output: std::env::temp_dir().join("transcript.wtns"),
+            ..NodeWitnessGenerator::new(root.join("toy_js/toy.wasm")).unwrap()
+        };
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+        let private_inputs = (0..2)
+            .map(|adder| HashMap::from([("adder".to_string(), serde_json::json!(adder))]))
+            .collect::<Vec<_>>();
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+
+        let mut transcript = Transcript::new();
+        let outcome = create_recursive_circuit_with_secondary(
+            &witness_generator,
+            r1cs,
+            private_inputs.clone(),
+            z0.clone(),
+            vec![C2::<G2>::default(); 2],
+            vec![Fq::from(0)],
+            &pp,
+            &mut transcript,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert!(matches!(outcome, ProvingOutcome::Completed(_)));
+
+        let mut jsonl = vec![];
+        transcript.write_jsonl(&mut jsonl).unwrap();
+        let transcript = Transcript::read_jsonl(jsonl.as_slice()).unwrap();
+        assert_eq!(transcript.entries.len(), 2);
+
+        // (10, 10) -> (10, 20) -> (11, 30)
+        let entry = &transcript.entries[1];
+        assert_eq!(entry.step, 1);
+        assert_eq!(entry.step_in, vec!["10".to_string(), "20".to_string()]);
+        assert_eq!(entry.step_out, vec!["11".to_string(), "30".to_string()]);
+        assert_eq!(
+            entry.private_input_sha256,
+            to_hex(&Sha256::digest(br#"{"adder":1}"#))
+        );
+
+        // an auditor can recompute the witness hash from the step's input alone
+        let input = CircomInput::new(&[Fr::from(10), Fr::from(20)], private_inputs[1].clone());
+        let witness: Vec<Fr> = witness_generator
+            .generate(&input, &CancellationToken::new())
+            .unwrap();
+        let mut hasher = Sha256::new();
+        witness
+            .iter()
+            .for_each(|value| hasher.update(value.to_repr()));
+        assert_eq!(entry.witness_sha256, to_hex(&hasher.finalize()));
+
+        // nested objects are hashed with sorted keys too
+        let input = serde_json::json!({"b": {"d": 1, "c": [{"f": 2, "e": 3}]}, "a": 4});
+        assert_eq!(
+            serde_json::to_string(&sort_keys(&input)).unwrap(),
+            r#"{"a":4,"b":{"c":[{"e":3,"f":2}],"d":1}}"#
+        );
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn transcript_across_failed_resume_matches_proof() {
+        use super::*;
+        use crate::cancel::CancellationToken;
+        use crate::circom::reader::load_r1cs;
+        use crate::witness::NodeWitnessGenerator;
+        use crate::{
+            continue_recursive_circuit_with_secondary, create_public_params,
+            create_recursive_circuit_with_secondary, FileLocation, C2, F,
+        };
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = F<G1>;
+        type Fq = F<G2>;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta");
+        let witness_generator = NodeWitnessGenerator {
+            witness_output: std::env::temp_dir().join("transcript_failed_resume.wtns"),
+            ..NodeWitnessGenerator::new(root.join("toy_js/toy.wasm")).unwrap()
+        };
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+        let adder = |adder: u64| HashMap::from([("adder".to_string(), serde_json::json!(adder))]);
+
+        let mut transcript = Transcript::new();
+        let mut recursive_proof = create_recursive_circuit_with_secondary(
+            &witness_generator,
+            r1cs.clone(),
+            vec![adder(0)],
+            vec![Fr::from(10), Fr::from(10)],
+            vec![C2::<G2>::default()],
+            vec![Fq::from(0)],
+            &pp,
+            &mut transcript,
+            &CancellationToken::new(),
+        )
+        .unwrap()
+        .into_recursive_proof();
+
+        // the second step lacks its private input, so the first one is kept
+        continue_recursive_circuit_with_secondary(
+            &mut recursive_proof,
+            &witness_generator,
+            r1cs.clone(),
+            vec![adder(1), HashMap::new(), adder(3)],
+            vec![C2::<G2>::default(); 3],
+            &pp,
+            &mut transcript,
+            &CancellationToken::new(),
+        )
+        .unwrap_err();
+        assert_eq!(recursive_proof.num_steps(), 2);
+
+        // retry from the failed step
+        let cancelled = continue_recursive_circuit_with_secondary(
+            &mut recursive_proof,
+            &witness_generator,
+            r1cs,
+            vec![adder(2), adder(3)],
+            vec![C2::<G2>::default(); 2],
+            &pp,
+            &mut transcript,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!(cancelled, None);
+
+        let mut jsonl = vec![];
+        transcript.write_jsonl(&mut jsonl).unwrap();
+        let transcript = Transcript::read_jsonl(jsonl.as_slice()).unwrap();
+
+        // (10, 10) -> (10, 20) -> (11, 30) -> (13, 41) -> (16, 54)
+        let z_n = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(transcript.entries.len(), recursive_proof.num_steps());
+        assert_eq!(
+            transcript.entries.last().unwrap().step_out,
+            to_decimal_strings(&z_n)
+        );
+        assert_eq!(
+            transcript.entries[0].step_in,
+            vec!["10".to_string(), "10".to_string()]
+        );
+        for (i, entries) in transcript.entries.windows(2).enumerate() {
+            assert_eq!(entries[0].step, i);
+            assert_eq!(entries[1].step_in, entries[0].step_out);
+        }
+        assert_eq!(z_n, vec![Fr::from(16), Fr::from(54)]);
+    }
+}
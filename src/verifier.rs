@@ -0,0 +1,207 @@
+//! Loading and verifying proofs of Circom step circuits. Without the default `prover` feature,
+//! only this module, `circom::circuit`, `export`, `proof` and `create_public_params` are built, so
+//! light clients can verify proofs without pulling in R1CS loading, witness generation or the
+//! filesystem.
+
+use std::io;
+
+use nova_snark::{
+    errors::NovaError,
+    traits::{snark::RelaxedR1CSSNARKTrait, Group},
+    PublicParams, RecursiveSNARK,
+};
+use serde::de::DeserializeOwned;
+
+use crate::proof::RecursiveProof;
+use crate::{C1, C2, F};
+
+/// Verifier key of a Circom step circuit with the default trivial secondary circuit.
+pub type VerifierKey<G1, G2, S1, S2> = nova_snark::VerifierKey<G1, G2, C1<G1>, C2<G2>, S1, S2>;
+pub type CompressedSNARK<G1, G2, S1, S2> =
+    nova_snark::CompressedSNARK<G1, G2, C1<G1>, C2<G2>, S1, S2>;
+
+/// Load public params serialized as JSON, to verify a `RecursiveSNARK`.
+pub fn load_public_params<G1, G2>(
+    json: &[u8],
+) -> Result<PublicParams<G1, G2, C1<G1>, C2<G2>>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+{
+    from_json(json)
+}
+
+/// Load a verifier key serialized as JSON, as returned by `CompressedSNARK::setup`.
+pub fn load_verifier_key<G1, G2, S1, S2>(
+    json: &[u8],
+) -> Result<VerifierKey<G1, G2, S1, S2>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    S1: RelaxedR1CSSNARKTrait<G1>,
+    S2: RelaxedR1CSSNARKTrait<G2>,
+{
+    from_json(json)
+}
+
+pub fn load_recursive_snark<G1, G2>(
+    json: &[u8],
+) -> Result<RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+{
+    from_json(json)
+}
+
+/// Load a `RecursiveProof` serialized as JSON.
+///
+/// **Its initial inputs and number of steps are read from the JSON, so `RecursiveProof::verify`
+/// only checks the proof against what its sender claims.** Check it with
+/// `RecursiveProof::verify_from` and the initial input and number of steps you expect.
+pub fn load_recursive_proof<G1, G2>(
+    json: &[u8],
+) -> Result<RecursiveProof<G1, G2, C1<G1>, C2<G2>>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+{
+    from_json(json)
+}
+
+pub fn load_compressed_snark<G1, G2, S1, S2>(
+    json: &[u8],
+) -> Result<CompressedSNARK<G1, G2, S1, S2>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    S1: RelaxedR1CSSNARKTrait<G1>,
+    S2: RelaxedR1CSSNARKTrait<G2>,
+{
+    from_json(json)
+}
+
+/// Verify the first `num_steps` steps of `recursive_snark` from `z0` and the secondary circuit's
+/// `z0_secondary` (`[0]` for the proving functions without `_with_secondary`), returning `z_n`.
+pub fn verify_recursive_snark<G1, G2>(
+    pp: &PublicParams<G1, G2, C1<G1>, C2<G2>>,
+    recursive_snark: &RecursiveSNARK<G1, G2, C1<G1>, C2<G2>>,
+    num_steps: usize,
+    z0: &[F<G1>],
+    z0_secondary: &[F<G2>],
+) -> Result<Vec<F<G1>>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+{
+    let (z_n, _) = recursive_snark
+        .verify(pp, num_steps, z0, z0_secondary)
+        .map_err(verification_error)?;
+    Ok(z_n)
+}
+
+/// Verify `num_steps` steps of `compressed_snark` from `z0` and the secondary circuit's
+/// `z0_secondary`, returning `z_n`.
+pub fn verify_compressed_snark<G1, G2, S1, S2>(
+    vk: &VerifierKey<G1, G2, S1, S2>,
+    compressed_snark: &CompressedSNARK<G1, G2, S1, S2>,
+    num_steps: usize,
+    z0: &[F<G1>],
+    z0_secondary: &[F<G2>],
+) -> Result<Vec<F<G1>>, io::Error>
+where
+    G1: Group<Base = <G2 as Group>::Scalar>,
+    G2: Group<Base = <G1 as Group>::Scalar>,
+    S1: RelaxedR1CSSNARKTrait<G1>,
+    S2: RelaxedR1CSSNARKTrait<G2>,
+{
+    let (z_n, _) = compressed_snark
+        .verify(vk, num_steps, z0.to_vec(), z0_secondary.to_vec())
+        .map_err(verification_error)?;
+    Ok(z_n)
+}
+
+fn from_json<T: DeserializeOwned>(json: &[u8]) -> Result<T, io::Error> {
+    Ok(serde_json::from_slice(json)?)
+}
+
+pub(crate) fn verification_error(err: NovaError) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("verification failed: {:?}", err),
+    )
+}
+
+#[cfg(feature = "prover")]
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn verify_serialized_proofs() {
+        use super::*;
+        use crate::circom::reader::load_r1cs;
+        use crate::{
+            compress_recursive_snark, create_public_params, create_recursive_circuit, FileLocation,
+            S,
+        };
+        use ff::Field;
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = F<G1>;
+        type Fq = F<G2>;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta");
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(root.join("toy.r1cs")));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+        let private_inputs = (0..2)
+            .map(|adder| HashMap::from([("adder".to_string(), serde_json::json!(adder))]))
+            .collect::<Vec<_>>();
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+        let recursive_proof = create_recursive_circuit(
+            FileLocation::PathBuf(root.join("toy_js/toy.wasm")),
+            r1cs,
+            private_inputs,
+            z0.clone(),
+            &pp,
+        )
+        .unwrap();
+        let recursive_snark = recursive_proof.recursive_snark();
+        let (pk, vk) = CompressedSNARK::<_, _, S<G1>, S<G2>>::setup(&pp).unwrap();
+        let compressed_snark = compress_recursive_snark(&pp, &pk, recursive_snark).unwrap();
+
+        // (10, 10) -> (10, 20) -> (11, 30)
+        let z_n = vec![Fr::from(11), Fr::from(30)];
+        let pp = load_public_params::<G1, G2>(&serde_json::to_vec(&pp).unwrap()).unwrap();
+        let recursive_snark =
+            load_recursive_snark(&serde_json::to_vec(recursive_snark).unwrap()).unwrap();
+        assert_eq!(
+            verify_recursive_snark(&pp, &recursive_snark, 2, &z0, &[Fq::ZERO]).unwrap(),
+            z_n
+        );
+        let recursive_proof =
+            load_recursive_proof(&serde_json::to_vec(&recursive_proof).unwrap()).unwrap();
+        assert_eq!(recursive_proof.verify(&pp).unwrap(), z_n);
+        assert_eq!(recursive_proof.verify_from(&pp, &z0, 2).unwrap(), z_n);
+        // a proof of fewer steps, or from another z0, than the verifier expects
+        assert!(recursive_proof.verify_from(&pp, &z0, 3).is_err());
+        assert!(recursive_proof
+            .verify_from(&pp, &[Fr::from(10), Fr::from(11)], 2)
+            .is_err());
+        assert!(verify_recursive_snark(&pp, &recursive_snark, 2, &z0, &[Fq::ONE]).is_err());
+
+        let vk =
+            load_verifier_key::<G1, G2, S<G1>, S<G2>>(&serde_json::to_vec(&vk).unwrap()).unwrap();
+        let compressed_snark =
+            load_compressed_snark(&serde_json::to_vec(&compressed_snark).unwrap()).unwrap();
+        assert_eq!(
+            verify_compressed_snark(&vk, &compressed_snark, 2, &z0, &[Fq::ZERO]).unwrap(),
+            z_n
+        );
+
+        let err =
+            verify_compressed_snark(&vk, &compressed_snark, 2, &z_n, &[Fq::ZERO]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,591 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    io,
+    path::PathBuf,
+};
+
+#[cfg(not(target_family = "wasm"))]
+use std::{env::current_dir, fs};
+
+use ff::PrimeField;
+use num_bigint::BigInt;
+use num_traits::Num;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cancel::CancellationToken;
+use crate::circom::reader::load_witness_from_file;
+#[cfg(not(target_family = "wasm"))]
+use crate::circom::reader::{
+    generate_witness_from_bin, generate_witness_from_wasm, WitnessGeneratorOptions,
+};
+use crate::circom::sym::InputSignals;
+use crate::{to_hex_strings, FileLocation};
+
+/// Input of a Circom step circuit: `step_in` as decimal strings, along with the private inputs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CircomInput {
+    pub step_in: Vec<String>,
+
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl CircomInput {
+    pub fn new<Fr: PrimeField>(step_in: &[Fr], private_input: HashMap<String, Value>) -> Self {
+        let step_in = to_hex_strings(step_in)
+            .iter()
+            .map(|x| BigInt::from_str_radix(x, 16).unwrap().to_str_radix(10))
+            .collect();
+
+        Self {
+            step_in,
+            extra: private_input,
+        }
+    }
+}
+
+/// Private inputs of one step, sent to the witness generator alongside `step_in`. Implemented for
+/// every `Serialize` type that serializes to a JSON object, such as `HashMap<String, Value>` or
+/// your own `#[derive(Serialize)]` struct, whose fields name the circuit's input signals.
+pub trait StepInput {
+    /// The input signals, by name.
+    fn to_signals(&self) -> Result<HashMap<String, Value>, io::Error>;
+}
+
+impl<T: Serialize> StepInput for T {
+    fn to_signals(&self) -> Result<HashMap<String, Value>, io::Error> {
+        match serde_json::to_value(self)? {
+            Value::Object(signals) => Ok(signals.into_iter().collect()),
+            value => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("private inputs must be a JSON object, not {}", value),
+            )),
+        }
+    }
+}
+
+/// The input signals of every step.
+pub(crate) fn to_signals<I: StepInput>(
+    private_inputs: &[I],
+) -> Result<Vec<HashMap<String, Value>>, io::Error> {
+    private_inputs.iter().map(StepInput::to_signals).collect()
+}
+
+/// Computes the witness of a Circom step circuit. Implement this to plug in your own witness
+/// source, e.g. a remote witness service or a Rust reimplementation of the circuit.
+#[cfg(not(target_family = "wasm"))]
+pub trait WitnessGenerator<Fr> {
+    /// Compute the full witness (starting with the constant `1`) of the circuit for `input`.
+    /// `cancel` is the proving run's token: a long-running generator should give up with an
+    /// `Interrupted` error once it is cancelled.
+    fn generate(
+        &self,
+        input: &CircomInput,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error>;
+}
+
+/// Computes the witness of a Circom step circuit. Implement this to plug in your own witness
+/// source, e.g. a remote witness service or a Rust reimplementation of the circuit.
+#[cfg(target_family = "wasm")]
+#[allow(async_fn_in_trait)]
+pub trait WitnessGenerator<Fr> {
+    /// Compute the full witness (starting with the constant `1`) of the circuit for `input`.
+    /// `cancel` is the proving run's token: a long-running generator should give up with an
+    /// `Interrupted` error once it is cancelled.
+    async fn generate(
+        &self,
+        input: &CircomInput,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error>;
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl<Fr, W: WitnessGenerator<Fr> + ?Sized> WitnessGenerator<Fr> for Box<W> {
+    fn generate(
+        &self,
+        input: &CircomInput,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        (**self).generate(input, cancel)
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl<Fr, W: WitnessGenerator<Fr> + ?Sized> WitnessGenerator<Fr> for &W {
+    fn generate(
+        &self,
+        input: &CircomInput,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        (**self).generate(input, cancel)
+    }
+}
+
+#[cfg(target_family = "wasm")]
+impl<Fr, W: WitnessGenerator<Fr> + ?Sized> WitnessGenerator<Fr> for &W {
+    async fn generate(
+        &self,
+        input: &CircomInput,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        (**self).generate(input, cancel).await
+    }
+}
+
+/// Checks the input signals of every step against the circuit's declared inputs before running
+/// `witness_generator`, so a misspelled or missing signal is reported by name instead of
+/// crashing the generator.
+#[derive(Clone)]
+pub struct CheckedWitnessGenerator<W> {
+    pub signals: InputSignals,
+    pub witness_generator: W,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl<Fr, W: WitnessGenerator<Fr>> WitnessGenerator<Fr> for CheckedWitnessGenerator<W> {
+    fn generate(
+        &self,
+        input: &CircomInput,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        self.signals.check(input)?;
+        self.witness_generator.generate(input, cancel)
+    }
+}
+
+#[cfg(target_family = "wasm")]
+impl<Fr, W: WitnessGenerator<Fr>> WitnessGenerator<Fr> for CheckedWitnessGenerator<W> {
+    async fn generate(
+        &self,
+        input: &CircomInput,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        self.signals.check(input)?;
+        self.witness_generator.generate(input, cancel).await
+    }
+}
+
+/// Runs a witness generator compiled with `circom --c`, killing it when the proving run is
+/// cancelled.
+#[cfg(not(target_family = "wasm"))]
+#[derive(Clone)]
+pub struct CppWitnessGenerator {
+    pub witness_bin: PathBuf,
+    /// Where the binary writes its `.wtns` file, removed once read.
+    pub witness_output: PathBuf,
+    pub options: WitnessGeneratorOptions,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl CppWitnessGenerator {
+    /// Writes the witness to `circom_witness.wtns` in the working directory.
+    pub fn new(witness_bin: impl Into<PathBuf>) -> Result<Self, io::Error> {
+        Ok(Self {
+            witness_bin: witness_bin.into(),
+            witness_output: current_dir()?.join("circom_witness.wtns"),
+            options: WitnessGeneratorOptions::default(),
+        })
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl<Fr: PrimeField> WitnessGenerator<Fr> for CppWitnessGenerator {
+    fn generate(
+        &self,
+        input: &CircomInput,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        let witness = generate_witness_from_bin(
+            &self.witness_bin,
+            &serde_json::to_string(input).unwrap(),
+            &self.witness_output,
+            &self.options,
+            cancel,
+        );
+        let _ = fs::remove_file(&self.witness_output);
+        witness
+    }
+}
+
+/// Runs a witness generator compiled with `circom --wasm` in node, killing it when the proving
+/// run is cancelled. A fetched or in-memory `witness_wasm` is written next to `witness_output` for
+/// node to run.
+#[cfg(not(target_family = "wasm"))]
+#[derive(Clone)]
+pub struct NodeWitnessGenerator {
+    pub witness_wasm: FileLocation,
+    /// Where node writes the `.wtns` file, removed once read.
+    pub witness_output: PathBuf,
+    pub options: WitnessGeneratorOptions,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl NodeWitnessGenerator {
+    /// Writes the witness to `circom_witness.wtns` in the working directory.
+    pub fn new(witness_wasm: impl Into<FileLocation>) -> Result<Self, io::Error> {
+        Ok(Self {
+            witness_wasm: witness_wasm.into(),
+            witness_output: current_dir()?.join("circom_witness.wtns"),
+            options: WitnessGeneratorOptions::default(),
+        })
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl<Fr: PrimeField> WitnessGenerator<Fr> for NodeWitnessGenerator {
+    fn generate(
+        &self,
+        input: &CircomInput,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        let witness = generate_witness_from_wasm(
+            &self.witness_wasm,
+            &serde_json::to_string(input).unwrap(),
+            &self.witness_output,
+            &self.options,
+            cancel,
+        );
+        let _ = fs::remove_file(&self.witness_output);
+        witness
+    }
+}
+
+/// Runs a witness generator compiled with `circom --wasm` in the browser, fetched from a URL or
+/// already in memory.
+#[cfg(target_family = "wasm")]
+#[derive(Clone)]
+pub struct BrowserWitnessGenerator {
+    pub witness_wasm: FileLocation,
+}
+
+#[cfg(target_family = "wasm")]
+impl BrowserWitnessGenerator {
+    pub fn new(witness_wasm_url: impl Into<String>) -> Self {
+        Self {
+            witness_wasm: FileLocation::URL(witness_wasm_url.into()),
+        }
+    }
+}
+
+#[cfg(target_family = "wasm")]
+impl<Fr: PrimeField> WitnessGenerator<Fr> for BrowserWitnessGenerator {
+    async fn generate(
+        &self,
+        input: &CircomInput,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        crate::circom::wasm::generate_witness_from_wasm(
+            &self.witness_wasm,
+            &serde_json::to_string(input).unwrap(),
+        )
+        .await
+    }
+}
+
+/// Witnesses computed ahead of time, stored in `.wtns` or JSON files, returned one per call in
+/// order regardless of the input.
+pub struct WitnessFiles {
+    pub files: Vec<PathBuf>,
+    next: Cell<usize>,
+}
+
+impl WitnessFiles {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        Self {
+            files,
+            next: Cell::new(0),
+        }
+    }
+
+    fn next_witness<Fr: PrimeField>(&self) -> Result<Vec<Fr>, io::Error> {
+        let file = self.files.get(self.next.get()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("only {} witness files", self.files.len()),
+            )
+        })?;
+        self.next.set(self.next.get() + 1);
+        load_witness_from_file(file)
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl<Fr: PrimeField> WitnessGenerator<Fr> for WitnessFiles {
+    fn generate(
+        &self,
+        _input: &CircomInput,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        self.next_witness()
+    }
+}
+
+#[cfg(target_family = "wasm")]
+impl<Fr: PrimeField> WitnessGenerator<Fr> for WitnessFiles {
+    async fn generate(
+        &self,
+        _input: &CircomInput,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        self.next_witness()
+    }
+}
+
+/// In-memory witnesses computed ahead of time, returned one per call in order regardless of the
+/// input.
+pub struct PrecomputedWitnesses<Fr> {
+    witnesses: RefCell<std::vec::IntoIter<Vec<Fr>>>,
+}
+
+impl<Fr> PrecomputedWitnesses<Fr> {
+    pub fn new(witnesses: Vec<Vec<Fr>>) -> Self {
+        Self {
+            witnesses: RefCell::new(witnesses.into_iter()),
+        }
+    }
+
+    fn next_witness(&self) -> Result<Vec<Fr>, io::Error> {
+        self.witnesses
+            .borrow_mut()
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "no witnesses left"))
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl<Fr: PrimeField> WitnessGenerator<Fr> for PrecomputedWitnesses<Fr> {
+    fn generate(
+        &self,
+        _input: &CircomInput,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        self.next_witness()
+    }
+}
+
+#[cfg(target_family = "wasm")]
+impl<Fr: PrimeField> WitnessGenerator<Fr> for PrecomputedWitnesses<Fr> {
+    async fn generate(
+        &self,
+        _input: &CircomInput,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<Fr>, io::Error> {
+        self.next_witness()
+    }
+}
+
+/// The witness generator for a Circom witness generator file, picked by its extension: node for
+/// `.wasm` files and the C++ binary otherwise. URLs and bytes are run with node, since a C++
+/// generator needs its `.dat` file alongside. The generator writes its witness to
+/// `witness_output`.
+#[cfg(not(target_family = "wasm"))]
+pub fn file_witness_generator<Fr: PrimeField>(
+    witness_generator_file: &FileLocation,
+    witness_output: PathBuf,
+) -> Box<dyn WitnessGenerator<Fr>> {
+    match witness_generator_file {
+        FileLocation::PathBuf(path) if path.extension().unwrap_or_default() != "wasm" => {
+            Box::new(CppWitnessGenerator {
+                witness_bin: path.clone(),
+                witness_output,
+                options: WitnessGeneratorOptions::default(),
+            })
+        }
+        location => Box::new(NodeWitnessGenerator {
+            witness_wasm: location.clone(),
+            witness_output,
+            options: WitnessGeneratorOptions::default(),
+        }),
+    }
+}
+
+/// The witness generator `create_recursive_circuit` and `continue_recursive_circuit` run for
+/// `witness_generator_file`, writing its witness to `circom_witness.wtns` in the working
+/// directory.
+#[cfg(not(target_family = "wasm"))]
+pub(crate) fn default_witness_generator<Fr: PrimeField>(
+    witness_generator_file: &FileLocation,
+) -> Result<Box<dyn WitnessGenerator<Fr>>, io::Error> {
+    Ok(file_witness_generator(
+        witness_generator_file,
+        current_dir()?.join("circom_witness.wtns"),
+    ))
+}
+
+/// The witness generator for a Circom `.wasm` witness generator served at a URL or in memory.
+#[cfg(target_family = "wasm")]
+pub fn file_witness_generator(
+    witness_generator_file: &FileLocation,
+) -> Result<BrowserWitnessGenerator, io::Error> {
+    match witness_generator_file {
+        FileLocation::PathBuf(path) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "can't run {} in the browser, pass a URL or bytes",
+                path.display()
+            ),
+        )),
+        location => Ok(BrowserWitnessGenerator {
+            witness_wasm: location.clone(),
+        }),
+    }
+}
+
+/// The witness generator `create_recursive_circuit` and `continue_recursive_circuit` run for
+/// `witness_generator_file`.
+#[cfg(target_family = "wasm")]
+pub(crate) fn default_witness_generator(
+    witness_generator_file: &FileLocation,
+) -> Result<BrowserWitnessGenerator, io::Error> {
+    file_witness_generator(witness_generator_file)
+}
+
+mod tests {
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn witness_files_replay_generated_witnesses() {
+        use super::*;
+        use crate::circom::reader::generate_witness_from_wasm;
+        use std::env::temp_dir;
+
+        type Fr = <pasta_curves::pallas::Point as nova_snark::traits::Group>::Scalar;
+
+        let input = CircomInput::new(
+            &[Fr::from(10), Fr::from(20)],
+            HashMap::from([("adder".to_string(), serde_json::json!(1))]),
+        );
+        assert_eq!(input.step_in, vec!["10", "20"]);
+
+        let witness_wasm =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples/toy/pasta/toy_js/toy.wasm");
+        let generator = NodeWitnessGenerator {
+            witness_output: temp_dir().join("nova_scotia_witness_test.wtns"),
+            ..NodeWitnessGenerator::new(witness_wasm.clone()).unwrap()
+        };
+        let witness: Vec<Fr> = generator
+            .generate(&input, &CancellationToken::new())
+            .unwrap();
+        // 1, then step_out = (11, 30), then step_in
+        assert_eq!(witness[..5], [1, 11, 30, 10, 20].map(Fr::from));
+
+        let witness_file = temp_dir().join("nova_scotia_witness_file_test.wtns");
+        generate_witness_from_wasm::<Fr>(
+            &FileLocation::PathBuf(witness_wasm),
+            &serde_json::to_string(&input).unwrap(),
+            &witness_file,
+            &WitnessGeneratorOptions::default(),
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        let witness_files = WitnessFiles::new(vec![witness_file.clone()]);
+        let replayed: Vec<Fr> = witness_files
+            .generate(&input, &CancellationToken::new())
+            .unwrap();
+        assert_eq!(replayed, witness);
+        let err =
+            WitnessGenerator::<Fr>::generate(&witness_files, &input, &CancellationToken::new())
+                .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        fs::remove_file(witness_file).unwrap();
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[test]
+    fn prove_from_precomputed_witnesses() {
+        use super::*;
+        use crate::circom::circuit::CircomCircuit;
+        use crate::circom::reader::{generate_witness_from_wasm, load_r1cs};
+        use crate::{
+            create_public_params, create_recursive_circuit_from_witness_files,
+            create_recursive_circuit_from_witnesses,
+        };
+        use ff::Field;
+        use std::env::temp_dir;
+
+        type G1 = pasta_curves::pallas::Point;
+        type G2 = pasta_curves::vesta::Point;
+        type Fr = <G1 as nova_snark::traits::Group>::Scalar;
+
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let r1cs = load_r1cs::<G1, G2>(&FileLocation::PathBuf(
+            root.join("examples/toy/pasta/toy.r1cs"),
+        ));
+        let witness_wasm = FileLocation::PathBuf(root.join("examples/toy/pasta/toy_js/toy.wasm"));
+        let pp = create_public_params::<G1, G2>(r1cs.clone());
+
+        // witnesses produced elsewhere, e.g. by a separate pipeline
+        let z0 = vec![Fr::from(10), Fr::from(10)];
+        let mut z = z0.clone();
+        let mut witnesses = vec![];
+        let mut witness_files = vec![];
+        for adder in 0..3 {
+            let input = CircomInput::new(
+                &z,
+                HashMap::from([("adder".to_string(), serde_json::json!(adder))]),
+            );
+            let witness_file = temp_dir().join(format!("nova_scotia_precomputed_{}.wtns", adder));
+            let witness = generate_witness_from_wasm::<Fr>(
+                &witness_wasm,
+                &serde_json::to_string(&input).unwrap(),
+                &witness_file,
+                &WitnessGeneratorOptions::default(),
+                &CancellationToken::new(),
+            )
+            .unwrap();
+            z = CircomCircuit {
+                r1cs: r1cs.clone(),
+                witness: Some(witness.clone()),
+            }
+            .get_public_outputs();
+            witnesses.push(witness);
+            witness_files.push(witness_file);
+        }
+
+        let recursive_proof = create_recursive_circuit_from_witnesses(
+            r1cs.clone(),
+            witnesses.clone(),
+            z0.clone(),
+            &pp,
+        )
+        .unwrap();
+        let z_n = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(z_n, z);
+
+        let recursive_proof = create_recursive_circuit_from_witness_files(
+            r1cs.clone(),
+            witness_files.clone(),
+            z0.clone(),
+            &pp,
+        )
+        .unwrap();
+        let z_n = recursive_proof.verify(&pp).unwrap();
+        assert_eq!(z_n, z);
+
+        let mut corrupted = witnesses.clone();
+        // step_out[1]; the toy R1CS's only constraint doesn't cover step_out[0]
+        corrupted[0][2] += Fr::ONE;
+        let err = create_recursive_circuit_from_witnesses(r1cs.clone(), corrupted, z0.clone(), &pp)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err
+            .to_string()
+            .starts_with("witness of step 0 does not satisfy constraint 0"));
+
+        witnesses.swap(1, 2);
+        let err = create_recursive_circuit_from_witnesses(r1cs, witnesses, z0, &pp)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err
+            .to_string()
+            .starts_with("witness of step 1 has step_in[0]"));
+
+        for witness_file in witness_files {
+            fs::remove_file(witness_file).unwrap();
+        }
+    }
+}